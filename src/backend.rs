@@ -0,0 +1,176 @@
+mod cinnamon;
+mod detect;
+mod gnome;
+mod gsettings_desktop;
+mod kde;
+mod mate;
+mod wlroots;
+mod x11;
+mod xfce;
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use clap::ValueEnum;
+use log::{error, info, trace};
+use serde::Deserialize;
+
+pub use detect::Selection;
+
+use crate::{
+    bus::SessionBus,
+    doctor::Check,
+    gsettings::GSettings,
+    theme::{Component, NightLight, Theme, ThemeSpec},
+};
+
+/// Desktop environments theme can be applied to
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Cinnamon,
+    Gnome,
+    Mate,
+    Xfce,
+    Kde,
+    Wlroots,
+    X11,
+}
+
+impl Display for BackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendKind::Cinnamon => f.write_str("cinnamon"),
+            BackendKind::Gnome => f.write_str("gnome"),
+            BackendKind::Mate => f.write_str("mate"),
+            BackendKind::Xfce => f.write_str("xfce"),
+            BackendKind::Kde => f.write_str("kde"),
+            BackendKind::Wlroots => f.write_str("wlroots"),
+            BackendKind::X11 => f.write_str("x11"),
+        }
+    }
+}
+
+/// Applies theme components to a particular desktop environment
+pub trait Backend {
+    fn kind(&self) -> BackendKind;
+
+    fn session_bus(&self) -> &SessionBus;
+
+    fn supports(&self, component: &Component) -> bool;
+
+    fn get(&self, component: &Component) -> Result<String, String>;
+
+    /// Whether component is currently set to given value
+    fn is_set_to(&self, component: &Component, value: &str) -> bool {
+        match (component, self.get(component)) {
+            (Component::Wallpaper, Ok(current)) => same_wallpaper(&current, value),
+            (_, Ok(current)) => current == value,
+            (_, Err(_)) => false,
+        }
+    }
+
+    fn set(&self, component: &Component, value: &str) -> Result<(), String>;
+
+    /// Environment checks specific to the backend, reported by `doctor` command
+    fn checks(&self) -> Vec<Check> {
+        Vec::new()
+    }
+}
+
+pub fn create(kind: BackendKind, bus: SessionBus) -> Box<dyn Backend> {
+    match kind {
+        BackendKind::Cinnamon => Box::new(cinnamon::new(GSettings::new(bus))),
+        BackendKind::Gnome => Box::new(gnome::new(GSettings::new(bus))),
+        BackendKind::Mate => Box::new(mate::new(GSettings::new(bus))),
+        BackendKind::Xfce => Box::new(xfce::Xfce::new(bus)),
+        BackendKind::Kde => Box::new(kde::Kde::new(bus)),
+        BackendKind::Wlroots => Box::new(wlroots::Wlroots::new(bus)),
+        BackendKind::X11 => Box::new(x11::X11::new(bus)),
+    }
+}
+
+/// Wallpaper as plain path, it is given either as file:// URI or as a path
+pub fn wallpaper_path(value: &str) -> &str {
+    value.strip_prefix("file://").unwrap_or(value)
+}
+
+/// Whether wallpapers are the same file, regardless of whether they are given as URIs or paths
+pub fn same_wallpaper(current: &str, value: &str) -> bool {
+    wallpaper_path(current) == wallpaper_path(value)
+}
+
+/// Sets all theme components that differ from current state
+pub fn set_theme(backend: &dyn Backend, theme: &Theme) {
+    for (component, value) in theme.spec.components() {
+        if !backend.supports(&component) {
+            info!(
+                "{} is not supported by {} backend -- skipping",
+                component,
+                backend.kind()
+            );
+            continue;
+        }
+
+        if backend.is_set_to(&component, &value) {
+            trace!("{} already set to: {}", component, value);
+            continue;
+        }
+
+        match backend.set(&component, &value) {
+            Ok(()) => info!("{} set to: {}", component, value),
+            Err(err) => error!("Failed to set {} to: {}. {}", component, value, err),
+        }
+    }
+}
+
+/// Whether all theme components supported by the backend are currently applied. Unsupported components
+/// are not taken into account, as backend has no way of reporting them.
+pub fn is_theme_applied(backend: &dyn Backend, theme: &Theme) -> bool {
+    theme
+        .spec
+        .components()
+        .iter()
+        .filter(|(component, _)| backend.supports(component))
+        .all(|(component, value)| backend.is_set_to(component, value))
+}
+
+/// Retrieves current state of all components supported by the backend. Components that failed to be
+/// retrieved hold error description, unsupported ones are left empty.
+pub fn get_theme(backend: &dyn Backend) -> ThemeSpec {
+    let get = |component: Component| {
+        if backend.supports(&component) {
+            backend.get(&component).unwrap_or_else(|err| err)
+        } else {
+            String::new()
+        }
+    };
+
+    ThemeSpec {
+        desktop: get(Component::Desktop),
+        mouse: get(Component::Mouse),
+        controls: get(Component::Controls),
+        icons: get(Component::Icons),
+        borders: get(Component::Borders),
+        wallpaper: get(Component::Wallpaper).into(),
+        kitty: None,
+        color_scheme_preference: get(Component::ColorScheme),
+        xresources: None,
+        interface_font: Some(get(Component::InterfaceFont)),
+        document_font: Some(get(Component::DocumentFont)),
+        monospace_font: Some(get(Component::MonospaceFont)),
+        titlebar_font: Some(get(Component::TitlebarFont)),
+        night_light: backend.supports(&Component::NightLight).then(|| NightLight {
+            enabled: get(Component::NightLight) == "true",
+            // Value may carry GVariant type annotation, e.g. "uint32 3500"
+            temperature: get(Component::NightLightTemperature)
+                .split_whitespace()
+                .last()
+                .and_then(|temperature| temperature.parse().ok()),
+        }),
+        gtk4_css: None,
+        gsettings: Vec::new(),
+        palette: BTreeMap::new(),
+        apps: BTreeMap::new(),
+    }
+}
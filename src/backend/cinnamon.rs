@@ -0,0 +1,48 @@
+use crate::gsettings::GSettings;
+
+use super::{
+    gsettings_desktop::{GSettingsDesktop, KeyMap, SchemaKey},
+    BackendKind,
+};
+
+const ORG_CINNAMON_DESKTOP_INTERFACE_SCHEMA: &str = "org.cinnamon.desktop.interface";
+const ORG_GNOME_DESKTOP_INTERFACE_SCHEMA: &str = "org.gnome.desktop.interface";
+const CINNAMON_COLOR_PLUGIN_SCHEMA: &str = "org.cinnamon.settings-daemon.plugins.color";
+
+const KEYS: KeyMap = KeyMap {
+    desktop: Some(SchemaKey::new("org.cinnamon.theme", "name")),
+    mouse: SchemaKey::new(ORG_CINNAMON_DESKTOP_INTERFACE_SCHEMA, "cursor-theme"),
+    controls: SchemaKey::new(ORG_CINNAMON_DESKTOP_INTERFACE_SCHEMA, "gtk-theme"),
+    icons: SchemaKey::new(ORG_CINNAMON_DESKTOP_INTERFACE_SCHEMA, "icon-theme"),
+    borders: SchemaKey::new("org.cinnamon.desktop.wm.preferences", "theme"),
+    wallpaper: &[SchemaKey::new("org.cinnamon.desktop.background", "picture-uri")],
+    wallpaper_uri: true,
+    // Cinnamon has no light / dark preference of its own, libadwaita apps read GNOME key
+    color_scheme: Some(SchemaKey::new(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, "color-scheme")),
+    interface_font: Some(SchemaKey::new(ORG_CINNAMON_DESKTOP_INTERFACE_SCHEMA, "font-name")),
+    // Cinnamon font settings dialog stores document & monospace fonts in GNOME schema
+    document_font: Some(SchemaKey::new(
+        ORG_GNOME_DESKTOP_INTERFACE_SCHEMA,
+        "document-font-name",
+    )),
+    monospace_font: Some(SchemaKey::new(
+        ORG_GNOME_DESKTOP_INTERFACE_SCHEMA,
+        "monospace-font-name",
+    )),
+    titlebar_font: Some(SchemaKey::new(
+        "org.cinnamon.desktop.wm.preferences",
+        "titlebar-font",
+    )),
+    night_light: Some(SchemaKey::new(
+        CINNAMON_COLOR_PLUGIN_SCHEMA,
+        "night-light-enabled",
+    )),
+    night_light_temperature: Some(SchemaKey::new(
+        CINNAMON_COLOR_PLUGIN_SCHEMA,
+        "night-light-temperature",
+    )),
+};
+
+pub fn new(gsettings: GSettings) -> GSettingsDesktop {
+    GSettingsDesktop::new(BackendKind::Cinnamon, gsettings, &KEYS)
+}
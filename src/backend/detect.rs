@@ -0,0 +1,98 @@
+use log::debug;
+
+use crate::{bus::SessionBus, process};
+
+use super::BackendKind;
+
+/// Backend picked for the current run along with the reason it was picked
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub kind: BackendKind,
+    pub reason: String,
+    /// Whether the backend is only a fallback, because none could be selected or detected
+    pub fallback: bool,
+}
+
+impl Selection {
+    /// Resolves backend in order of precedence: command line, config, detection, default
+    pub fn resolve(cli: Option<BackendKind>, config: Option<BackendKind>, bus: &SessionBus) -> Selection {
+        if let Some(kind) = cli {
+            return Selection::new(kind, "selected with --backend".to_owned());
+        }
+        if let Some(kind) = config {
+            return Selection::new(kind, "selected in config".to_owned());
+        }
+        if let Some((kind, reason)) = detect(bus) {
+            return Selection::new(kind, reason);
+        }
+
+        Selection {
+            kind: BackendKind::default(),
+            reason: "desktop environment not detected, using default".to_owned(),
+            fallback: true,
+        }
+    }
+
+    fn new(kind: BackendKind, reason: String) -> Selection {
+        Selection {
+            kind,
+            reason,
+            fallback: false,
+        }
+    }
+}
+
+/// Maps desktop name, as found in XDG_CURRENT_DESKTOP or DESKTOP_SESSION, to a backend
+fn backend_for_desktop_name(name: &str) -> Option<BackendKind> {
+    let name = name.to_lowercase();
+    let name = name.trim_start_matches("x-");
+
+    match name {
+        "cinnamon" | "cinnamon2d" | "cinnamon-wayland" => Some(BackendKind::Cinnamon),
+        "gnome" | "gnome-xorg" | "gnome-wayland" | "ubuntu" | "ubuntu-xorg" => Some(BackendKind::Gnome),
+        "mate" => Some(BackendKind::Mate),
+        "xfce" | "xfce4" | "xubuntu" => Some(BackendKind::Xfce),
+        "kde" | "plasma" | "plasmawayland" | "plasmax11" => Some(BackendKind::Kde),
+        "sway" | "hyprland" | "river" | "wlroots" => Some(BackendKind::Wlroots),
+        "i3" | "bspwm" | "openbox" | "awesome" | "herbstluftwm" => Some(BackendKind::X11),
+        _ => None,
+    }
+}
+
+/// Maps name of a process started by desktop session to a backend
+fn backend_for_process_name(name: &str) -> Option<BackendKind> {
+    match name {
+        "cinnamon-session" | "cinnamon" => Some(BackendKind::Cinnamon),
+        "gnome-shell" => Some(BackendKind::Gnome),
+        "mate-session" => Some(BackendKind::Mate),
+        "xfce4-session" => Some(BackendKind::Xfce),
+        "plasmashell" | "ksmserver" => Some(BackendKind::Kde),
+        "sway" | "Hyprland" | "river" => Some(BackendKind::Wlroots),
+        "i3" | "bspwm" | "openbox" | "awesome" | "herbstluftwm" => Some(BackendKind::X11),
+        _ => None,
+    }
+}
+
+/// Detects desktop environment of the session. Returns detected backend & description of how it was found.
+pub fn detect(bus: &SessionBus) -> Option<(BackendKind, String)> {
+    // Our own environment says nothing about other user's session
+    if bus.user.is_none() {
+        for var in ["XDG_CURRENT_DESKTOP", "DESKTOP_SESSION"] {
+            if let Ok(value) = std::env::var(var) {
+                // XDG_CURRENT_DESKTOP holds colon separated list of names, e.g. "X-Cinnamon" or "ubuntu:GNOME"
+                if let Some(kind) = value.split(':').find_map(backend_for_desktop_name) {
+                    return Some((kind, format!("detected from {}={}", var, value)));
+                }
+                debug!("Unrecognized desktop {}={}", var, value);
+            }
+        }
+    }
+
+    process::list_processes()
+        .iter()
+        .filter(|proc| proc.uid == bus.uid())
+        .find_map(|proc| {
+            backend_for_process_name(&proc.name)
+                .map(|kind| (kind, format!("detected from running {} process", proc.name)))
+        })
+}
@@ -0,0 +1,48 @@
+use crate::gsettings::GSettings;
+
+use super::{
+    gsettings_desktop::{GSettingsDesktop, KeyMap, SchemaKey},
+    BackendKind,
+};
+
+const ORG_GNOME_DESKTOP_INTERFACE_SCHEMA: &str = "org.gnome.desktop.interface";
+const ORG_GNOME_DESKTOP_BACKGROUND_SCHEMA: &str = "org.gnome.desktop.background";
+const GNOME_COLOR_PLUGIN_SCHEMA: &str = "org.gnome.settings-daemon.plugins.color";
+
+const KEYS: KeyMap = KeyMap {
+    // Requires "User Themes" shell extension
+    desktop: Some(SchemaKey::new("org.gnome.shell.extensions.user-theme", "name")),
+    mouse: SchemaKey::new(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, "cursor-theme"),
+    controls: SchemaKey::new(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, "gtk-theme"),
+    icons: SchemaKey::new(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, "icon-theme"),
+    borders: SchemaKey::new("org.gnome.desktop.wm.preferences", "theme"),
+    // GNOME picks wallpaper depending on color scheme, so both are set to the same picture
+    wallpaper: &[
+        SchemaKey::new(ORG_GNOME_DESKTOP_BACKGROUND_SCHEMA, "picture-uri"),
+        SchemaKey::new(ORG_GNOME_DESKTOP_BACKGROUND_SCHEMA, "picture-uri-dark"),
+    ],
+    wallpaper_uri: true,
+    color_scheme: Some(SchemaKey::new(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, "color-scheme")),
+    interface_font: Some(SchemaKey::new(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, "font-name")),
+    document_font: Some(SchemaKey::new(
+        ORG_GNOME_DESKTOP_INTERFACE_SCHEMA,
+        "document-font-name",
+    )),
+    monospace_font: Some(SchemaKey::new(
+        ORG_GNOME_DESKTOP_INTERFACE_SCHEMA,
+        "monospace-font-name",
+    )),
+    titlebar_font: Some(SchemaKey::new(
+        "org.gnome.desktop.wm.preferences",
+        "titlebar-font",
+    )),
+    night_light: Some(SchemaKey::new(GNOME_COLOR_PLUGIN_SCHEMA, "night-light-enabled")),
+    night_light_temperature: Some(SchemaKey::new(
+        GNOME_COLOR_PLUGIN_SCHEMA,
+        "night-light-temperature",
+    )),
+};
+
+pub fn new(gsettings: GSettings) -> GSettingsDesktop {
+    GSettingsDesktop::new(BackendKind::Gnome, gsettings, &KEYS)
+}
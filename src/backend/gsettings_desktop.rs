@@ -0,0 +1,165 @@
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    gsettings::{self, GSettings},
+    theme::Component,
+};
+
+use super::{Backend, BackendKind};
+
+/// Location of a single setting
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaKey {
+    pub schema: &'static str,
+    pub key: &'static str,
+}
+
+impl SchemaKey {
+    pub const fn new(schema: &'static str, key: &'static str) -> Self {
+        SchemaKey { schema, key }
+    }
+}
+
+/// Keys theme components are stored under in a given desktop environment
+pub struct KeyMap {
+    pub desktop: Option<SchemaKey>,
+    pub mouse: SchemaKey,
+    pub controls: SchemaKey,
+    pub icons: SchemaKey,
+    pub borders: SchemaKey,
+    /// All of the keys are set to the same value, the first one is used for retrieval
+    pub wallpaper: &'static [SchemaKey],
+    /// Whether wallpaper is stored as file:// URI or plain path
+    pub wallpaper_uri: bool,
+    pub color_scheme: Option<SchemaKey>,
+    pub interface_font: Option<SchemaKey>,
+    pub document_font: Option<SchemaKey>,
+    pub monospace_font: Option<SchemaKey>,
+    pub titlebar_font: Option<SchemaKey>,
+    pub night_light: Option<SchemaKey>,
+    pub night_light_temperature: Option<SchemaKey>,
+}
+
+impl KeyMap {
+    fn keys_for(&self, component: &Component) -> Vec<SchemaKey> {
+        match component {
+            Component::Desktop => self.desktop.into_iter().collect(),
+            Component::Mouse => vec![self.mouse],
+            Component::Controls => vec![self.controls],
+            Component::Icons => vec![self.icons],
+            Component::Borders => vec![self.borders],
+            Component::Wallpaper => self.wallpaper.to_vec(),
+            Component::ColorScheme => self.color_scheme.into_iter().collect(),
+            Component::InterfaceFont => self.interface_font.into_iter().collect(),
+            Component::DocumentFont => self.document_font.into_iter().collect(),
+            Component::MonospaceFont => self.monospace_font.into_iter().collect(),
+            Component::TitlebarFont => self.titlebar_font.into_iter().collect(),
+            Component::NightLight => self.night_light.into_iter().collect(),
+            Component::NightLightTemperature => self.night_light_temperature.into_iter().collect(),
+            Component::Xresources | Component::GSettings { .. } => Vec::new(),
+        }
+    }
+
+    fn all_keys(&self) -> Vec<SchemaKey> {
+        Component::ALL
+            .iter()
+            .flat_map(|component| self.keys_for(component))
+            .collect()
+    }
+}
+
+/// Backend for desktop environments storing their settings in GSettings
+pub struct GSettingsDesktop {
+    kind: BackendKind,
+    gsettings: GSettings,
+    keys: &'static KeyMap,
+}
+
+impl GSettingsDesktop {
+    pub fn new(kind: BackendKind, gsettings: GSettings, keys: &'static KeyMap) -> Self {
+        GSettingsDesktop {
+            kind,
+            gsettings,
+            keys,
+        }
+    }
+
+    fn format_wallpaper(&self, path: &str) -> String {
+        if self.keys.wallpaper_uri {
+            if path.starts_with("file://") {
+                path.to_owned()
+            } else {
+                format!("file://{}", path)
+            }
+        } else {
+            super::wallpaper_path(path).to_owned()
+        }
+    }
+}
+
+impl Backend for GSettingsDesktop {
+    fn kind(&self) -> BackendKind {
+        self.kind
+    }
+
+    fn session_bus(&self) -> &SessionBus {
+        self.gsettings.session_bus()
+    }
+
+    fn supports(&self, component: &Component) -> bool {
+        matches!(component, Component::GSettings { .. }) || !self.keys.keys_for(component).is_empty()
+    }
+
+    fn get(&self, component: &Component) -> Result<String, String> {
+        if let Component::GSettings { schema, key } = component {
+            return self.gsettings.get(schema, key);
+        }
+
+        match self.keys.keys_for(component).first() {
+            Some(key) => self.gsettings.get(key.schema, key.key),
+            None => Err(format!("{} is not supported", component)),
+        }
+    }
+
+    fn is_set_to(&self, component: &Component, value: &str) -> bool {
+        match (component, self.get(component)) {
+            (Component::Wallpaper, Ok(current)) => super::same_wallpaper(&current, value),
+            // Numeric keys are printed with type annotation, e.g. "uint32 3500"
+            (_, Ok(current)) => gsettings::values_equal(&current, value),
+            (_, Err(_)) => false,
+        }
+    }
+
+    fn set(&self, component: &Component, value: &str) -> Result<(), String> {
+        if let Component::GSettings { schema, key } = component {
+            self.gsettings.validate(schema, key, value)?;
+            return self.gsettings.set(schema, key, value);
+        }
+
+        let value = match component {
+            Component::Wallpaper => self.format_wallpaper(value),
+            _ => value.to_owned(),
+        };
+
+        self.keys
+            .keys_for(component)
+            .iter()
+            .try_for_each(|key| self.gsettings.set(key.schema, key.key, &value))
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let keys: Vec<(&str, &str)> = self
+            .keys
+            .all_keys()
+            .iter()
+            .map(|key| (key.schema, key.key))
+            .collect();
+
+        let mut checks = vec![doctor::check_binary(
+            "gsettings",
+            "install the glib2 tools package providing gsettings",
+        )];
+        checks.extend(doctor::check_schema_keys(&self.gsettings, &keys));
+        checks
+    }
+}
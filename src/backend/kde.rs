@@ -0,0 +1,245 @@
+use std::{path::PathBuf, process::Stdio};
+
+use log::warn;
+
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    ini::IniFile,
+    theme::Component,
+    util,
+};
+
+use super::{Backend, BackendKind};
+
+const KDEGLOBALS: &str = "kdeglobals";
+const KCMINPUTRC: &str = "kcminputrc";
+const KWINRC: &str = "kwinrc";
+const PLASMA_APPLETS_RC: &str = "plasma-org.kde.plasma.desktop-appletsrc";
+
+/// Values of KGlobalSettings::ChangeType enum
+const CHANGE_PALETTE: i32 = 0;
+const CHANGE_SETTINGS: i32 = 3;
+const CHANGE_ICON: i32 = 4;
+const CHANGE_CURSOR: i32 = 5;
+
+/// Backend for KDE Plasma. Settings are written directly to Plasma config files, after which running
+/// Plasma is notified over D-Bus to pick the changes up.
+pub struct Kde {
+    bus: SessionBus,
+}
+
+impl Kde {
+    pub fn new(bus: SessionBus) -> Self {
+        Kde { bus }
+    }
+
+    /// (file, section, key) a component is stored under
+    fn entry_for(component: &Component) -> Option<(&'static str, &'static str, &'static str)> {
+        match component {
+            Component::Desktop => Some((KDEGLOBALS, "KDE", "LookAndFeelPackage")),
+            Component::Controls => Some((KDEGLOBALS, "General", "ColorScheme")),
+            Component::Icons => Some((KDEGLOBALS, "Icons", "Theme")),
+            Component::Mouse => Some((KCMINPUTRC, "Mouse", "cursorTheme")),
+            Component::Borders => Some((KWINRC, "org.kde.kdecoration2", "theme")),
+            _ => None,
+        }
+    }
+
+    fn config_path(&self, file: &str) -> Result<PathBuf, String> {
+        self.bus
+            .config_dir()
+            .map(|dir| dir.join(file))
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())
+    }
+
+    fn read(&self, file: &str, section: &str, key: &str) -> Result<String, String> {
+        let path = self.config_path(file)?;
+        IniFile::load(&path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?
+            .get(section, key)
+            .ok_or_else(|| format!("{} is not set in {}", key, path.display()))
+    }
+
+    fn write(&self, file: &str, section: &str, key: &str, value: &str) -> Result<(), String> {
+        let path = self.config_path(file)?;
+        let mut ini =
+            IniFile::load(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        ini.set(section, key, value);
+        ini.save(&self.bus, &path)
+    }
+
+    fn dbus_send(&self, args: &[&str]) -> Result<(), String> {
+        let result = self
+            .bus
+            .command("dbus-send")
+            .arg("--session")
+            .args(args)
+            .stdout(Stdio::null())
+            .status();
+
+        util::check_status(result)
+    }
+
+    /// Broadcasts KGlobalSettings change notification, which makes running KDE apps reload given settings
+    fn notify_change(&self, change_type: i32) -> Result<(), String> {
+        self.dbus_send(&[
+            "--type=signal",
+            "/KGlobalSettings",
+            "org.kde.KGlobalSettings.notifyChange",
+            &format!("int32:{}", change_type),
+            "int32:0",
+        ])
+    }
+
+    fn reconfigure_kwin(&self) -> Result<(), String> {
+        self.dbus_send(&[
+            "--type=method_call",
+            "--dest=org.kde.KWin",
+            "/KWin",
+            "org.kde.KWin.reconfigure",
+        ])
+    }
+
+    fn apply_color_scheme(&self, scheme: &str) -> Result<(), String> {
+        // Writing the key alone does not regenerate colors in kdeglobals, dedicated tool does
+        if util::find_executable("plasma-apply-colorscheme").is_some() {
+            let result = self
+                .bus
+                .command("plasma-apply-colorscheme")
+                .arg(scheme)
+                .stdout(Stdio::null())
+                .status();
+            return util::check_status(result);
+        }
+
+        warn!("plasma-apply-colorscheme not found, only updating ColorScheme key");
+        self.write(KDEGLOBALS, "General", "ColorScheme", scheme)?;
+        self.notify_change(CHANGE_PALETTE)
+    }
+
+    fn apply_look_and_feel(&self, package: &str) -> Result<(), String> {
+        // Plasma only records LookAndFeelPackage key, the global theme is applied by dedicated tool
+        if util::find_executable("plasma-apply-lookandfeel").is_some() {
+            let result = self
+                .bus
+                .command("plasma-apply-lookandfeel")
+                .arg("-a")
+                .arg(package)
+                .stdout(Stdio::null())
+                .status();
+            return util::check_status(result);
+        }
+
+        warn!("plasma-apply-lookandfeel not found, only updating LookAndFeelPackage key");
+        self.write(KDEGLOBALS, "KDE", "LookAndFeelPackage", package)?;
+        self.notify_change(CHANGE_SETTINGS)
+    }
+
+    /// KDecoration plugin drawing given window decoration theme. Aurorae themes are named
+    /// `__aurorae__svg__<name>`, native decorations are their own plugins, e.g. theme `Breeze` of
+    /// `org.kde.breeze`.
+    fn decoration_library(theme: &str) -> String {
+        if theme.starts_with("__aurorae__") {
+            "org.kde.kwin.aurorae".to_owned()
+        } else {
+            format!("org.kde.{}", theme.to_lowercase())
+        }
+    }
+
+    fn get_wallpaper(&self) -> Result<String, String> {
+        let path = self.config_path(PLASMA_APPLETS_RC)?;
+        IniFile::load(&path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?
+            .find(
+                |section| section.ends_with("][Wallpaper][org.kde.image][General"),
+                "Image",
+            )
+            .ok_or_else(|| format!("No wallpaper found in {}", path.display()))
+    }
+
+    fn set_wallpaper(&self, path: &str) -> Result<(), String> {
+        let uri = if path.starts_with("file://") {
+            path.to_owned()
+        } else {
+            format!("file://{}", path)
+        };
+
+        let script = format!(
+            "desktops().forEach(d => {{ \
+                d.wallpaperPlugin = 'org.kde.image'; \
+                d.currentConfigGroup = ['Wallpaper', 'org.kde.image', 'General']; \
+                d.writeConfig('Image', {}); \
+            }});",
+            // JSON string is a valid JS string literal, with backslashes and quotes escaped
+            serde_json::to_string(&uri).map_err(|err| err.to_string())?
+        );
+
+        self.dbus_send(&[
+            "--type=method_call",
+            "--dest=org.kde.plasmashell",
+            "/PlasmaShell",
+            "org.kde.PlasmaShell.evaluateScript",
+            &format!("string:{}", script),
+        ])
+    }
+}
+
+impl Backend for Kde {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Kde
+    }
+
+    fn session_bus(&self) -> &SessionBus {
+        &self.bus
+    }
+
+    fn supports(&self, component: &Component) -> bool {
+        matches!(component, Component::Wallpaper) || Kde::entry_for(component).is_some()
+    }
+
+    fn get(&self, component: &Component) -> Result<String, String> {
+        if let Component::Wallpaper = component {
+            return self.get_wallpaper();
+        }
+
+        match Kde::entry_for(component) {
+            Some((file, section, key)) => self.read(file, section, key),
+            None => Err(format!("{} is not supported", component)),
+        }
+    }
+
+    fn set(&self, component: &Component, value: &str) -> Result<(), String> {
+        match component {
+            Component::Wallpaper => self.set_wallpaper(value),
+            Component::Controls => self.apply_color_scheme(value),
+            Component::Desktop => self.apply_look_and_feel(value),
+            Component::Icons => {
+                self.write(KDEGLOBALS, "Icons", "Theme", value)?;
+                self.notify_change(CHANGE_ICON)
+            }
+            Component::Mouse => {
+                self.write(KCMINPUTRC, "Mouse", "cursorTheme", value)?;
+                self.notify_change(CHANGE_CURSOR)
+            }
+            Component::Borders => {
+                self.write(
+                    KWINRC,
+                    "org.kde.kdecoration2",
+                    "library",
+                    &Kde::decoration_library(value),
+                )?;
+                self.write(KWINRC, "org.kde.kdecoration2", "theme", value)?;
+                self.reconfigure_kwin()
+            }
+            _ => Err(format!("{} is not supported", component)),
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        vec![doctor::check_binary(
+            "dbus-send",
+            "install dbus package providing dbus-send",
+        )]
+    }
+}
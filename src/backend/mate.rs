@@ -0,0 +1,31 @@
+use crate::gsettings::GSettings;
+
+use super::{
+    gsettings_desktop::{GSettingsDesktop, KeyMap, SchemaKey},
+    BackendKind,
+};
+
+const ORG_MATE_INTERFACE_SCHEMA: &str = "org.mate.interface";
+
+const KEYS: KeyMap = KeyMap {
+    // MATE panel & desktop follow the GTK theme, there is no separate desktop theme
+    desktop: None,
+    mouse: SchemaKey::new("org.mate.peripherals-mouse", "cursor-theme"),
+    controls: SchemaKey::new(ORG_MATE_INTERFACE_SCHEMA, "gtk-theme"),
+    icons: SchemaKey::new(ORG_MATE_INTERFACE_SCHEMA, "icon-theme"),
+    borders: SchemaKey::new("org.mate.Marco.general", "theme"),
+    wallpaper: &[SchemaKey::new("org.mate.background", "picture-filename")],
+    wallpaper_uri: false,
+    color_scheme: Some(SchemaKey::new("org.gnome.desktop.interface", "color-scheme")),
+    interface_font: Some(SchemaKey::new(ORG_MATE_INTERFACE_SCHEMA, "font-name")),
+    document_font: Some(SchemaKey::new(ORG_MATE_INTERFACE_SCHEMA, "document-font-name")),
+    monospace_font: Some(SchemaKey::new(ORG_MATE_INTERFACE_SCHEMA, "monospace-font-name")),
+    titlebar_font: Some(SchemaKey::new("org.mate.Marco.general", "titlebar-font")),
+    // MATE settings daemon has no night light plugin
+    night_light: None,
+    night_light_temperature: None,
+};
+
+pub fn new(gsettings: GSettings) -> GSettingsDesktop {
+    GSettingsDesktop::new(BackendKind::Mate, gsettings, &KEYS)
+}
@@ -0,0 +1,334 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use log::{debug, warn};
+
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    gsettings::GSettings,
+    ini::IniFile,
+    process,
+    theme::Component,
+    util,
+};
+
+use super::{Backend, BackendKind};
+
+const GTK_SETTINGS_SECTION: &str = "Settings";
+const GTK_SETTINGS_FILES: &[&str] = &["gtk-3.0/settings.ini", "gtk-4.0/settings.ini"];
+const ORG_GNOME_DESKTOP_INTERFACE_SCHEMA: &str = "org.gnome.desktop.interface";
+const DEFAULT_CURSOR_SIZE: &str = "24";
+/// Session environment compositor IPC & wallpaper tools rely on, it is missing when run from cron
+const SESSION_ENV: &[&str] = &["WAYLAND_DISPLAY", "SWAYSOCK", "HYPRLAND_INSTANCE_SIGNATURE"];
+/// How long new swaybg instance has to keep running before the previous ones are stopped
+const SWAYBG_STARTUP: Duration = Duration::from_millis(500);
+
+/// Compositors supporting runtime cursor theme change over IPC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compositor {
+    Sway,
+    Hyprland,
+    Other,
+}
+
+impl Compositor {
+    fn detect(bus: &SessionBus, env: &[(&str, String)]) -> Compositor {
+        let is_set = |key: &str| env.iter().any(|(name, _)| *name == key);
+        if is_set("SWAYSOCK") {
+            return Compositor::Sway;
+        }
+        if is_set("HYPRLAND_INSTANCE_SIGNATURE") {
+            return Compositor::Hyprland;
+        }
+
+        let processes = process::list_processes();
+        let is_running = |name: &str| {
+            processes
+                .iter()
+                .any(|proc| proc.name == name && proc.uid == bus.uid())
+        };
+
+        if is_running("sway") {
+            Compositor::Sway
+        } else if is_running("Hyprland") {
+            Compositor::Hyprland
+        } else {
+            Compositor::Other
+        }
+    }
+}
+
+/// Backend for wlroots based compositors (Sway, Hyprland, etc.), which have no settings daemon. GTK reads
+/// its settings from settings.ini files, cursor is changed through compositor IPC and wallpaper through
+/// swww or swaybg.
+pub struct Wlroots {
+    bus: SessionBus,
+    compositor: Compositor,
+    /// Variables of SESSION_ENV found in the session, passed to spawned commands
+    env: Vec<(&'static str, String)>,
+}
+
+impl Wlroots {
+    pub fn new(bus: SessionBus) -> Self {
+        let env: Vec<(&'static str, String)> = SESSION_ENV
+            .iter()
+            .filter_map(|key| bus.session_env_var(key).map(|value| (*key, value)))
+            .collect();
+        let compositor = Compositor::detect(&bus, &env);
+        debug!("Detected compositor: {:?}", compositor);
+        Wlroots { bus, compositor, env }
+    }
+
+    /// Creates command connected to the compositor of the session
+    fn session_command(&self, program: &str) -> Command {
+        let mut command = self.bus.command(program);
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+
+    /// settings.ini key & GSettings key a component is stored under
+    fn keys_for(component: &Component) -> Option<(&'static str, &'static str)> {
+        match component {
+            Component::Controls => Some(("gtk-theme-name", "gtk-theme")),
+            Component::Icons => Some(("gtk-icon-theme-name", "icon-theme")),
+            Component::Mouse => Some(("gtk-cursor-theme-name", "cursor-theme")),
+            Component::ColorScheme => Some(("gtk-application-prefer-dark-theme", "color-scheme")),
+            _ => None,
+        }
+    }
+
+    fn gtk_settings_paths(&self) -> Result<Vec<PathBuf>, String> {
+        let config_dir = self
+            .bus
+            .config_dir()
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())?;
+        Ok(GTK_SETTINGS_FILES
+            .iter()
+            .map(|file| config_dir.join(file))
+            .collect())
+    }
+
+    fn get_gtk_setting(&self, key: &str) -> Result<String, String> {
+        // GTK 3 & 4 files are kept in sync, GTK 3 one is the reference
+        let path = &self.gtk_settings_paths()?[0];
+        IniFile::load(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?
+            .get(GTK_SETTINGS_SECTION, key)
+            .ok_or_else(|| format!("{} is not set in {}", key, path.display()))
+    }
+
+    fn set_gtk_setting(&self, key: &str, value: &str) -> Result<(), String> {
+        for path in self.gtk_settings_paths()? {
+            let mut ini =
+                IniFile::load(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+            ini.set(GTK_SETTINGS_SECTION, key, value);
+            ini.save(&self.bus, &path)?;
+        }
+        Ok(())
+    }
+
+    /// GTK on Wayland prefers GSettings over settings.ini whenever the schema is installed, so the value is
+    /// mirrored there when possible
+    fn mirror_to_gsettings(&self, key: &str, value: &str) {
+        if util::find_executable("gsettings").is_none() {
+            return;
+        }
+        let gsettings = GSettings::new(self.bus.clone());
+        if let Err(err) = gsettings.set(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA, key, value) {
+            debug!("Failed to mirror {} to GSettings: {}", key, err);
+        }
+    }
+
+    fn set_cursor(&self, theme: &str) -> Result<(), String> {
+        let size = self
+            .bus
+            .session_env_var("XCURSOR_SIZE")
+            .unwrap_or_else(|| DEFAULT_CURSOR_SIZE.to_owned());
+
+        let result = match self.compositor {
+            Compositor::Sway => self
+                .session_command("swaymsg")
+                .arg(format!("seat * xcursor_theme {} {}", sway_quote(theme), size))
+                .stdout(Stdio::null())
+                .status(),
+            Compositor::Hyprland => self
+                .session_command("hyprctl")
+                .arg("setcursor")
+                .arg(theme)
+                .arg(&size)
+                .stdout(Stdio::null())
+                .status(),
+            Compositor::Other => {
+                warn!("Unknown compositor, cursor theme is applied only to newly started applications");
+                return Ok(());
+            }
+        };
+        util::check_status(result)
+    }
+
+    fn get_wallpaper(&self) -> Result<String, String> {
+        if util::find_executable("swww").is_some() {
+            let result = self
+                .session_command("swww")
+                .arg("query")
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .output();
+            // Output lines look like: "eDP-1: 1920x1080, scale: 1, currently displaying: image: /path"
+            return util::handle_get_result(result)?
+                .lines()
+                .find_map(|line| line.split_once("image: ").map(|(_, path)| path.trim().to_owned()))
+                .ok_or_else(|| "swww does not display any image".to_owned());
+        }
+
+        process::find_processes("swaybg", self.bus.uid())
+            .iter()
+            .find_map(|proc| {
+                let cmdline = proc.cmdline();
+                cmdline
+                    .iter()
+                    .position(|arg| arg == "-i" || arg == "--image")
+                    .and_then(|index| cmdline.get(index + 1).cloned())
+            })
+            .ok_or_else(|| "No running swww or swaybg instance found".to_owned())
+    }
+
+    fn set_wallpaper(&self, path: &str) -> Result<(), String> {
+        let path = super::wallpaper_path(path);
+
+        if util::find_executable("swww").is_some() {
+            let result = self.session_command("swww").arg("img").arg(path).status();
+            return util::check_status(result);
+        }
+
+        if util::find_executable("swaybg").is_some() {
+            // swaybg has no IPC - new instance is started and the old ones are stopped once it survived its
+            // start-up, so that output is not left without wallpaper when the new one fails
+            let previous = process::find_processes("swaybg", self.bus.uid());
+            let mut swaybg = self
+                .session_command("swaybg")
+                .arg("--image")
+                .arg(path)
+                .arg("--mode")
+                .arg("fill")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|err| format!("Failed to start swaybg: {}", err))?;
+
+            thread::sleep(SWAYBG_STARTUP);
+            if let Ok(Some(status)) = swaybg.try_wait() {
+                return Err(format!("swaybg exited right after start with {}", status));
+            }
+            for proc in previous {
+                if let Err(err) = proc.signal(libc::SIGTERM) {
+                    warn!("Failed to stop previous swaybg ({}): {}", proc.pid, err);
+                }
+            }
+            return Ok(());
+        }
+
+        Err("Neither swww nor swaybg is installed".to_owned())
+    }
+}
+
+/// Quotes argument of sway command, so that names with spaces are passed as a single argument
+fn sway_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Backend for Wlroots {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Wlroots
+    }
+
+    fn session_bus(&self) -> &SessionBus {
+        &self.bus
+    }
+
+    fn supports(&self, component: &Component) -> bool {
+        matches!(component, Component::Wallpaper) || Wlroots::keys_for(component).is_some()
+    }
+
+    fn get(&self, component: &Component) -> Result<String, String> {
+        match component {
+            Component::Wallpaper => self.get_wallpaper(),
+            Component::ColorScheme => {
+                let prefer_dark = self.get_gtk_setting("gtk-application-prefer-dark-theme")?;
+                match prefer_dark.as_str() {
+                    "1" | "true" => Ok("prefer-dark".to_owned()),
+                    _ => Ok("default".to_owned()),
+                }
+            }
+            _ => match Wlroots::keys_for(component) {
+                Some((key, _)) => self.get_gtk_setting(key),
+                None => Err(format!("{} is not supported", component)),
+            },
+        }
+    }
+
+    fn is_set_to(&self, component: &Component, value: &str) -> bool {
+        match component {
+            // settings.ini only tells dark preference apart, light one reads back as default
+            Component::ColorScheme => {
+                let is_dark = |value: &str| value == "prefer-dark";
+                matches!(self.get(component), Ok(current) if is_dark(&current) == is_dark(value))
+            }
+            Component::Wallpaper => {
+                matches!(self.get(component), Ok(current) if super::same_wallpaper(&current, value))
+            }
+            _ => matches!(self.get(component), Ok(current) if current == value),
+        }
+    }
+
+    fn set(&self, component: &Component, value: &str) -> Result<(), String> {
+        match component {
+            Component::Wallpaper => self.set_wallpaper(value),
+            Component::ColorScheme => {
+                let prefer_dark = if value == "prefer-dark" { "true" } else { "false" };
+                self.set_gtk_setting("gtk-application-prefer-dark-theme", prefer_dark)?;
+                self.mirror_to_gsettings("color-scheme", value);
+                Ok(())
+            }
+            _ => {
+                let (key, gsettings_key) =
+                    Wlroots::keys_for(component).ok_or_else(|| format!("{} is not supported", component))?;
+                self.set_gtk_setting(key, value)?;
+                self.mirror_to_gsettings(gsettings_key, value);
+                if let Component::Mouse = component {
+                    self.set_cursor(value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let mut checks = Vec::new();
+        match self.compositor {
+            Compositor::Sway => checks.push(doctor::check_binary("swaymsg", "install sway")),
+            Compositor::Hyprland => checks.push(doctor::check_binary("hyprctl", "install Hyprland")),
+            Compositor::Other => checks.push(Check::fail(
+                "Compositor",
+                "neither Sway nor Hyprland is running",
+                "cursor theme can only be changed at runtime on Sway & Hyprland",
+            )),
+        }
+
+        if util::find_executable("swww").is_some() {
+            checks.push(doctor::check_binary("swww", "install swww"));
+        } else {
+            checks.push(doctor::check_binary(
+                "swaybg",
+                "install swww or swaybg to change wallpaper",
+            ));
+        }
+        checks
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use log::{info, warn};
+
+use crate::{
+    bus::SessionBus,
+    constant::ConstantRepo,
+    doctor::{self, Check},
+    ini::IniFile,
+    process,
+    theme::Component,
+    util,
+};
+
+use super::{Backend, BackendKind};
+
+/// Used when DISPLAY of the session can not be found
+const DEFAULT_DISPLAY: &str = ":0";
+const XRESOURCES_STATE_FILE: &str = "xresources";
+
+/// Backend for plain X11 window managers (i3, bspwm, etc.). GTK settings are served by xsettingsd, X
+/// resources are merged with xrdb and wallpaper is set with feh or nitrogen.
+pub struct X11 {
+    bus: SessionBus,
+    /// X display of the session, commands talking to X server are pointed to it
+    display: String,
+}
+
+impl X11 {
+    pub fn new(bus: SessionBus) -> Self {
+        // DISPLAY is usually missing when run from cron
+        let display = bus.session_env_var("DISPLAY").unwrap_or_else(|| {
+            warn!(
+                "Failed to find DISPLAY of the session, falling back to {}",
+                DEFAULT_DISPLAY
+            );
+            DEFAULT_DISPLAY.to_owned()
+        });
+        X11 { bus, display }
+    }
+
+    fn xsetting_for(component: &Component) -> Option<&'static str> {
+        match component {
+            Component::Controls => Some("Net/ThemeName"),
+            Component::Icons => Some("Net/IconThemeName"),
+            Component::Mouse => Some("Gtk/CursorThemeName"),
+            _ => None,
+        }
+    }
+
+    fn home_dir(&self) -> Result<PathBuf, String> {
+        self.bus
+            .home_dir()
+            .ok_or_else(|| "Failed to resolve home directory".to_owned())
+    }
+
+    /// xsettingsd reads ~/.xsettingsd if it exists, XDG location otherwise
+    fn xsettingsd_config(&self) -> Result<PathBuf, String> {
+        let legacy = self.home_dir()?.join(".xsettingsd");
+        if legacy.is_file() {
+            return Ok(legacy);
+        }
+        self.bus
+            .config_dir()
+            .map(|dir| dir.join("xsettingsd").join("xsettingsd.conf"))
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())
+    }
+
+    /// Command talking to the X server of the session
+    fn x_command(&self, program: &str) -> Command {
+        let mut command = self.bus.command(program);
+        command.env("DISPLAY", &self.display);
+        command
+    }
+
+    /// Records path & content of last merged Xresources file. X resource database does not tell which file it
+    /// was loaded from, and it lives as long as the X session, as does the runtime directory.
+    fn xresources_state(&self) -> PathBuf {
+        self.bus
+            .runtime_dir()
+            .join(ConstantRepo::app_name())
+            .join(XRESOURCES_STATE_FILE)
+    }
+
+    fn get_xsetting(&self, name: &str) -> Result<String, String> {
+        let path = self.xsettingsd_config()?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+
+        content
+            .lines()
+            .find_map(|line| {
+                let (key, value) = line.trim().split_once(char::is_whitespace)?;
+                (key == name).then(|| value.trim().trim_matches('"').to_owned())
+            })
+            .ok_or_else(|| format!("{} is not set in {}", name, path.display()))
+    }
+
+    fn set_xsetting(&self, name: &str, value: &str) -> Result<(), String> {
+        let path = self.xsettingsd_config()?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(format!("Failed to read {}: {}", path.display(), err)),
+        };
+
+        let entry = format!("{} \"{}\"", name, value);
+        let mut found = false;
+        let mut lines: Vec<String> = content
+            .lines()
+            .map(|line| match line.trim().split_once(char::is_whitespace) {
+                Some((key, _)) if key == name => {
+                    found = true;
+                    entry.clone()
+                }
+                _ => line.to_owned(),
+            })
+            .collect();
+        if !found {
+            lines.push(entry);
+        }
+
+        self.bus.write_file(&path, lines.join("\n") + "\n")?;
+
+        self.reload_xsettingsd();
+        Ok(())
+    }
+
+    fn reload_xsettingsd(&self) {
+        let daemons = process::find_processes("xsettingsd", self.bus.uid());
+        if daemons.is_empty() {
+            warn!("xsettingsd is not running, settings will be applied when it is started");
+        }
+        for daemon in daemons {
+            if let Err(err) = daemon.signal(libc::SIGHUP) {
+                warn!("Failed to reload xsettingsd ({}): {}", daemon.pid, err);
+            }
+        }
+    }
+
+    fn merge_xresources(&self, path: &str) -> Result<(), String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read Xresources file {}: {}", path, err))?;
+        let result = self.x_command("xrdb").arg("-merge").arg(path).status();
+        util::check_status(result)?;
+
+        let state = format!("{}\n{}", path, content);
+        if let Err(err) = self.bus.write_file(&self.xresources_state(), state) {
+            warn!("Failed to record merged Xresources: {}", err);
+        }
+        Ok(())
+    }
+
+    /// Path of last merged Xresources file, if its content did not change since
+    fn merged_xresources(&self) -> Result<String, String> {
+        let path = self.xresources_state();
+        let state = std::fs::read_to_string(&path)
+            .map_err(|err| format!("No Xresources merged in this session: {}", err))?;
+        let (merged, content) = state
+            .split_once('\n')
+            .ok_or_else(|| format!("Invalid content of {}", path.display()))?;
+        match std::fs::read_to_string(merged) {
+            Ok(current) if current == content => Ok(merged.to_owned()),
+            _ => Err(format!("{} changed since it was merged", merged)),
+        }
+    }
+
+    fn get_wallpaper(&self) -> Result<String, String> {
+        let home = self.home_dir()?;
+
+        // ~/.fehbg is a shell script ending with: feh --no-fehbg --bg-fill '/path/to/image'
+        if let Ok(fehbg) = std::fs::read_to_string(home.join(".fehbg")) {
+            if let Some(path) = fehbg
+                .lines()
+                .filter(|line| line.trim_start().starts_with("feh"))
+                .find_map(|line| line.rsplit('\'').nth(1))
+            {
+                return Ok(path.to_owned());
+            }
+        }
+
+        if let Some(config_dir) = self.bus.config_dir() {
+            let saved = config_dir.join("nitrogen").join("bg-saved.cfg");
+            if let Some(path) = IniFile::load(&saved)
+                .ok()
+                .and_then(|ini| ini.find(|_| true, "file"))
+            {
+                return Ok(path);
+            }
+        }
+
+        Err("No wallpaper saved by feh or nitrogen found".to_owned())
+    }
+
+    fn set_wallpaper(&self, path: &str) -> Result<(), String> {
+        let path = super::wallpaper_path(path);
+
+        let result = if util::find_executable("feh").is_some() {
+            self.x_command("feh").arg("--bg-fill").arg(path).status()
+        } else if util::find_executable("nitrogen").is_some() {
+            self.x_command("nitrogen")
+                .arg("--set-zoom-fill")
+                .arg("--save")
+                .arg(path)
+                .stdout(Stdio::null())
+                .status()
+        } else {
+            return Err("Neither feh nor nitrogen is installed".to_owned());
+        };
+        util::check_status(result)
+    }
+}
+
+impl Backend for X11 {
+    fn kind(&self) -> BackendKind {
+        BackendKind::X11
+    }
+
+    fn session_bus(&self) -> &SessionBus {
+        &self.bus
+    }
+
+    fn supports(&self, component: &Component) -> bool {
+        matches!(component, Component::Wallpaper | Component::Xresources)
+            || X11::xsetting_for(component).is_some()
+    }
+
+    fn get(&self, component: &Component) -> Result<String, String> {
+        match component {
+            Component::Wallpaper => self.get_wallpaper(),
+            Component::Xresources => self.merged_xresources(),
+            _ => match X11::xsetting_for(component) {
+                Some(name) => self.get_xsetting(name),
+                None => Err(format!("{} is not supported", component)),
+            },
+        }
+    }
+
+    fn set(&self, component: &Component, value: &str) -> Result<(), String> {
+        match component {
+            Component::Wallpaper => self.set_wallpaper(value),
+            Component::Xresources => {
+                self.merge_xresources(value)?;
+                info!("Xresources merged, restart applications to pick them up");
+                Ok(())
+            }
+            _ => match X11::xsetting_for(component) {
+                Some(name) => self.set_xsetting(name, value),
+                None => Err(format!("{} is not supported", component)),
+            },
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let mut checks = vec![
+            doctor::check_binary("xsettingsd", "install xsettingsd"),
+            doctor::check_binary("xrdb", "install xrdb (x11-xserver-utils)"),
+        ];
+        if util::find_executable("nitrogen").is_some() {
+            checks.push(doctor::check_binary("nitrogen", "install feh or nitrogen"));
+        } else {
+            checks.push(doctor::check_binary("feh", "install feh or nitrogen"));
+        }
+        checks
+    }
+}
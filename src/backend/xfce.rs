@@ -0,0 +1,141 @@
+use std::process::Stdio;
+
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    theme::Component,
+    util,
+};
+
+use super::{Backend, BackendKind};
+
+const XSETTINGS_CHANNEL: &str = "xsettings";
+const XFWM4_CHANNEL: &str = "xfwm4";
+const XFCE4_DESKTOP_CHANNEL: &str = "xfce4-desktop";
+
+/// Backend for XFCE, which keeps its settings in xfconf rather than GSettings
+pub struct Xfce {
+    bus: SessionBus,
+}
+
+impl Xfce {
+    pub fn new(bus: SessionBus) -> Self {
+        Xfce { bus }
+    }
+
+    fn property_for(component: &Component) -> Option<(&'static str, &'static str)> {
+        match component {
+            Component::Controls => Some((XSETTINGS_CHANNEL, "/Net/ThemeName")),
+            Component::Icons => Some((XSETTINGS_CHANNEL, "/Net/IconThemeName")),
+            Component::Mouse => Some((XSETTINGS_CHANNEL, "/Gtk/CursorThemeName")),
+            Component::Borders => Some((XFWM4_CHANNEL, "/general/theme")),
+            _ => None,
+        }
+    }
+
+    fn query(&self, channel: &str, property: &str) -> Result<String, String> {
+        let result = self
+            .bus
+            .command("xfconf-query")
+            .arg("--channel")
+            .arg(channel)
+            .arg("--property")
+            .arg(property)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output();
+
+        util::handle_get_result(result)
+    }
+
+    fn update(&self, channel: &str, property: &str, value: &str) -> Result<(), String> {
+        // --create makes it work for properties that have never been changed from their defaults
+        let result = self
+            .bus
+            .command("xfconf-query")
+            .arg("--channel")
+            .arg(channel)
+            .arg("--property")
+            .arg(property)
+            .arg("--create")
+            .arg("--type")
+            .arg("string")
+            .arg("--set")
+            .arg(value)
+            .status();
+
+        util::check_status(result)
+    }
+
+    /// Lists wallpaper properties of all monitors & workspaces
+    fn backdrop_properties(&self) -> Result<Vec<String>, String> {
+        let result = self
+            .bus
+            .command("xfconf-query")
+            .arg("--channel")
+            .arg(XFCE4_DESKTOP_CHANNEL)
+            .arg("--list")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output();
+
+        let properties: Vec<String> = util::handle_get_result(result)?
+            .lines()
+            .filter(|property| property.starts_with("/backdrop/") && property.ends_with("/last-image"))
+            .map(str::to_owned)
+            .collect();
+
+        if properties.is_empty() {
+            return Err("No backdrop properties found in xfce4-desktop channel".to_owned());
+        }
+        Ok(properties)
+    }
+}
+
+impl Backend for Xfce {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Xfce
+    }
+
+    fn session_bus(&self) -> &SessionBus {
+        &self.bus
+    }
+
+    fn supports(&self, component: &Component) -> bool {
+        matches!(component, Component::Wallpaper) || Xfce::property_for(component).is_some()
+    }
+
+    fn get(&self, component: &Component) -> Result<String, String> {
+        if let Component::Wallpaper = component {
+            let properties = self.backdrop_properties()?;
+            return self.query(XFCE4_DESKTOP_CHANNEL, &properties[0]);
+        }
+
+        match Xfce::property_for(component) {
+            Some((channel, property)) => self.query(channel, property),
+            None => Err(format!("{} is not supported", component)),
+        }
+    }
+
+    fn set(&self, component: &Component, value: &str) -> Result<(), String> {
+        if let Component::Wallpaper = component {
+            let path = super::wallpaper_path(value);
+            return self
+                .backdrop_properties()?
+                .iter()
+                .try_for_each(|property| self.update(XFCE4_DESKTOP_CHANNEL, property, path));
+        }
+
+        match Xfce::property_for(component) {
+            Some((channel, property)) => self.update(channel, property, value),
+            None => Err(format!("{} is not supported", component)),
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        vec![doctor::check_binary(
+            "xfconf-query",
+            "install xfconf package providing xfconf-query",
+        )]
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    cell::OnceCell,
+    ffi::{CStr, CString},
+    os::unix::{fs::FileTypeExt, process::CommandExt},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use libc::geteuid;
+use log::{debug, info, warn};
+
+use crate::process;
+
+pub const DBUS_SESSION_BUS_ADDRESS_KEY: &str = "DBUS_SESSION_BUS_ADDRESS";
+
+/// Processes that are started as a part of graphical session. Their environment is the most reliable
+/// source of session bus address.
+const SESSION_PROCESSES: &[&str] = &[
+    "cinnamon-session",
+    "cinnamon",
+    "gnome-session-b",
+    "gnome-shell",
+    "mate-session",
+    "xfce4-session",
+    "plasmashell",
+    "ksmserver",
+    "sway",
+    "Hyprland",
+    "i3",
+];
+
+/// System user, as described by passwd database
+#[derive(Clone, Debug)]
+pub struct User {
+    pub name: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub home: PathBuf,
+}
+
+impl User {
+    pub fn from_name(name: &str) -> Option<User> {
+        let c_name = CString::new(name).ok()?;
+        // SAFETY: getpwnam returns either null or pointer to a valid passwd entry, which we copy out of
+        // before any other call to passwd functions is made.
+        unsafe { User::from_passwd(libc::getpwnam(c_name.as_ptr())) }
+    }
+
+    pub fn from_uid(uid: u32) -> Option<User> {
+        // SAFETY: see from_name
+        unsafe { User::from_passwd(libc::getpwuid(uid)) }
+    }
+
+    unsafe fn from_passwd(passwd: *const libc::passwd) -> Option<User> {
+        if passwd.is_null() {
+            return None;
+        }
+        let passwd = &*passwd;
+        Some(User {
+            name: CStr::from_ptr(passwd.pw_name).to_string_lossy().into_owned(),
+            uid: passwd.pw_uid,
+            gid: passwd.pw_gid,
+            home: PathBuf::from(CStr::from_ptr(passwd.pw_dir).to_string_lossy().into_owned()),
+        })
+    }
+}
+
+/// Session bus desktop settings are accessed through
+#[derive(Clone, Debug)]
+pub struct SessionBus {
+    /// User owning the session, set only when it is different from the one running the tool
+    pub user: Option<User>,
+    /// Whether own environment describes the session, i.e. it was not explicitly asked for other user's one
+    own_session: bool,
+    /// Address along with where it was found (e.g. environment variable or a session process), discovered
+    /// once it is first needed
+    address: OnceCell<(String, String)>,
+}
+
+impl SessionBus {
+    /// Resolves session owner. When `user_name` is given (which requires root privileges) session of that
+    /// user is used. When running as root via sudo, session of the invoking user is used.
+    pub fn resolve(user_name: Option<&str>) -> Result<SessionBus, String> {
+        let euid = unsafe { geteuid() };
+
+        let user = match user_name {
+            Some(name) => {
+                let user = User::from_name(name).ok_or_else(|| format!("Unknown user: {}", name))?;
+                if euid != 0 && euid != user.uid {
+                    return Err(format!(
+                        "Running on behalf of user {} requires root privileges",
+                        name
+                    ));
+                }
+                Some(user)
+            }
+            None if euid == 0 => std::env::var("SUDO_UID")
+                .ok()
+                .and_then(|uid| uid.parse::<u32>().ok())
+                .and_then(User::from_uid),
+            None => None,
+        };
+        Ok(SessionBus {
+            user: user.filter(|user| user.uid != euid),
+            own_session: user_name.is_none(),
+            address: OnceCell::new(),
+        })
+    }
+
+    /// Bus address, looked up in environment, runtime directory and session processes
+    pub fn address(&self) -> &str {
+        &self.discover().0
+    }
+
+    /// Where the address was found
+    pub fn origin(&self) -> &str {
+        &self.discover().1
+    }
+
+    fn discover(&self) -> &(String, String) {
+        self.address.get_or_init(|| {
+            let uid = self.uid();
+            debug!("Resolving session bus for uid: {}", uid);
+            let (address, origin) = discover_address(uid, self.own_session, &self.runtime_dir());
+            info!("Using session bus {} (found in {})", address, origin);
+            (address, origin)
+        })
+    }
+
+    /// Home directory of the session owner
+    pub fn home_dir(&self) -> Option<PathBuf> {
+        match &self.user {
+            Some(user) => Some(user.home.clone()),
+            None => dirs::home_dir(),
+        }
+    }
+
+    /// Runtime directory of the session owner, where per-session sockets live
+    pub fn runtime_dir(&self) -> PathBuf {
+        match (&self.user, std::env::var_os("XDG_RUNTIME_DIR")) {
+            (None, Some(dir)) => PathBuf::from(dir),
+            _ => PathBuf::from(format!("/run/user/{}", self.uid())),
+        }
+    }
+
+    /// Config directory of the session owner
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        match &self.user {
+            Some(user) => Some(user.home.join(".config")),
+            None => dirs::config_dir(),
+        }
+    }
+
+    /// Data directory of the session owner
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        match &self.user {
+            Some(user) => Some(user.home.join(".local").join("share")),
+            None => dirs::data_dir(),
+        }
+    }
+
+    /// Id of the user owning the session
+    pub fn uid(&self) -> u32 {
+        match &self.user {
+            Some(user) => user.uid,
+            None => unsafe { geteuid() },
+        }
+    }
+
+    /// Value of environment variable in the session, e.g. DISPLAY. Own environment is used unless running on
+    /// behalf of other user, session processes are looked up otherwise.
+    pub fn session_env_var(&self, key: &str) -> Option<String> {
+        if self.user.is_none() {
+            if let Ok(value) = std::env::var(key) {
+                return Some(value);
+            }
+        }
+        let (value, origin) = env_of_session_process(self.uid(), key)?;
+        debug!("Using {}={} (found in {})", key, value, origin);
+        Some(value)
+    }
+
+    /// Creates command connected to the session bus, run as the session owner if needed
+    pub fn command(&self, program: &str) -> Command {
+        let mut command = self.user_command(program);
+        command.env(DBUS_SESSION_BUS_ADDRESS_KEY, self.address());
+        command
+    }
+
+    /// Creates command run as the session owner if needed, for tools that do not need the bus
+    pub fn user_command(&self, program: &str) -> Command {
+        let mut command = Command::new(program);
+        if let Some(user) = &self.user {
+            command
+                .uid(user.uid)
+                .gid(user.gid)
+                .env("HOME", &user.home)
+                .env("USER", &user.name)
+                .env("XDG_RUNTIME_DIR", format!("/run/user/{}", user.uid));
+        }
+        command
+    }
+
+    /// Makes the tool access files with privileges of the session owner, if it is other user. Files written
+    /// then belong to them and links they planted in their home can not redirect reads or writes outside of
+    /// what they can access themselves. Spawned commands are run as the owner anyway, see `command`.
+    pub fn act_as_owner(&self) -> Result<(), String> {
+        let user = match &self.user {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+        debug!("Accessing files as user {} ({})", user.name, user.uid);
+
+        // SAFETY: plain syscalls. Supplementary groups are dropped first, as groups of root would still
+        // grant access otherwise.
+        unsafe {
+            if libc::setgroups(1, &user.gid) != 0 {
+                return Err(format!(
+                    "Failed to drop supplementary groups: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            libc::setfsgid(user.gid);
+            libc::setfsuid(user.uid);
+            // Both calls report previous id rather than failure, invalid id queries the current one
+            if libc::setfsgid(u32::MAX) != user.gid as i32 || libc::setfsuid(u32::MAX) != user.uid as i32 {
+                return Err(format!("Failed to access files as user {}", user.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates directory along with its missing parents
+    pub fn create_dir_all(&self, dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(dir).map_err(|err| format!("Failed to create {}: {}", dir.display(), err))
+    }
+
+    /// Writes file, creating its parent directories if needed
+    pub fn write_file(&self, path: &Path, content: impl AsRef<[u8]>) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+        std::fs::write(path, content).map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+    }
+
+    /// Creates symlink at `link` pointing to `source`
+    pub fn symlink(&self, source: &Path, link: &Path) -> Result<(), String> {
+        std::os::unix::fs::symlink(source, link)
+            .map_err(|err| format!("Failed to link {}: {}", link.display(), err))
+    }
+}
+
+/// Looks bus address up, along with where it was found. Environment variable describes our own session only.
+fn discover_address(uid: u32, own_session: bool, runtime_dir: &Path) -> (String, String) {
+    if own_session {
+        if let Ok(address) = std::env::var(DBUS_SESSION_BUS_ADDRESS_KEY) {
+            return (address, "environment".to_owned());
+        }
+    }
+
+    let socket = runtime_dir.join("bus");
+    if is_socket(&socket) {
+        return (
+            format!("unix:path={}", socket.display()),
+            "runtime directory".to_owned(),
+        );
+    }
+
+    if let Some(found) = address_from_session_process(uid) {
+        return found;
+    }
+
+    warn!("Failed to discover session bus address, falling back to default one");
+    (format!("unix:path=/run/user/{}/bus", uid), "default".to_owned())
+}
+
+fn is_socket(path: &Path) -> bool {
+    path.metadata()
+        .map(|meta| meta.file_type().is_socket())
+        .unwrap_or(false)
+}
+
+/// Reads bus address from environment of a running session process of given user
+fn address_from_session_process(uid: u32) -> Option<(String, String)> {
+    env_of_session_process(uid, DBUS_SESSION_BUS_ADDRESS_KEY)
+}
+
+/// Reads environment variable of a running session process of given user, along with description of the
+/// process it was found in
+fn env_of_session_process(uid: u32, key: &str) -> Option<(String, String)> {
+    let mut candidates: Vec<process::ProcessInfo> = process::list_processes()
+        .into_iter()
+        .filter(|proc| proc.uid == uid)
+        .collect();
+
+    // Session processes first, then any other process of that user
+    candidates.sort_by_key(|proc| !SESSION_PROCESSES.contains(&proc.name.as_str()));
+
+    candidates.iter().find_map(|proc| {
+        proc.env_var(key)
+            .map(|value| (value, format!("environment of {} ({})", proc.name, proc.pid)))
+    })
+}
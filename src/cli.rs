@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
-use crate::{command::Commands, util};
+use crate::{backend::BackendKind, command::Commands, util};
 
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about)]
@@ -23,6 +23,15 @@ pub struct Args {
     #[arg(long, default_value_t = String::from("info"))]
     pub log_level: String,
 
+    /// Apply changes to the session of given user. Requires root privileges - useful when running from
+    /// root's crontab. When run via sudo, session of the invoking user is used by default.
+    #[arg(long, value_name = "NAME")]
+    pub user: Option<String>,
+
+    /// Desktop environment to apply theme to. Overrides the one selected in config or detected.
+    #[arg(long, value_enum)]
+    pub backend: Option<BackendKind>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
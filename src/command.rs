@@ -9,6 +9,7 @@ pub enum Commands {
     },
 
     /// Retrieves current configuration and prints it to logfile or stdout
+    #[command(alias = "status")]
     Get,
 
     /// Opens config file in a editor allowing for modification
@@ -25,4 +26,28 @@ pub enum Commands {
 
     /// Removes theme lock so that auto-selection does work again
     Unlock,
+
+    /// Lists themes defined in config
+    List {
+        /// Print the list in JSON format
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Lists themes, icon sets & cursors installed in the system
+    Available {
+        /// Print the list in JSON format
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Checks whether environment is set up properly & reports remedies for detected problems
+    Doctor,
+
+    /// Switches between light & dark theme defined in the "toggle" section of config
+    Toggle {
+        /// Lock the applied theme until the next time span boundary
+        #[arg(long, default_value_t = false)]
+        lock: bool,
+    },
 }
@@ -10,8 +10,12 @@ use log::{error, info};
 use serde::Deserialize;
 
 use crate::{
+    backend::BackendKind,
+    bus::SessionBus,
     constant::ConstantRepo,
-    theme::{Theme, TimeSpec},
+    discovery::{self, InstalledThemes},
+    template::Template,
+    theme::{Component, Theme, TimeSpec},
 };
 
 pub type Result<T> = std::result::Result<T, ConfigError>;
@@ -35,10 +39,40 @@ impl Display for ConfigError {
     }
 }
 
+/// Problem found while validating config against the system state
+#[derive(Clone, Debug)]
+pub struct ValidationIssue {
+    pub theme: String,
+    pub component: Component,
+    pub value: String,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Theme {}: {} \"{}\" is not installed",
+            self.theme, self.component, self.value
+        )
+    }
+}
+
+/// Pair of themes `toggle` command switches between
+#[derive(Debug, Deserialize)]
+pub struct TogglePair {
+    pub light: String,
+    pub dark: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     themes: Vec<Theme>,
     default: Option<String>,
+    toggle: Option<TogglePair>,
+    backend: Option<BackendKind>,
+    /// Files rendered with palette of applied theme
+    #[serde(default)]
+    templates: Vec<Template>,
 }
 
 impl Config {
@@ -47,8 +81,9 @@ impl Config {
             ConfigError::FileError(path.to_str().unwrap_or("(unknown)").to_owned(), err.kind())
         })?;
         let reader = BufReader::new(file);
-        let config: Config =
+        let mut config: Config =
             serde_json::from_reader(reader).map_err(|err| ConfigError::InvalidFormat(err.to_string()))?;
+        config.themes.iter_mut().for_each(|theme| theme.spec.migrate());
         Ok(config)
     }
 
@@ -68,6 +103,75 @@ impl Config {
         }
         None
     }
+
+    pub fn themes(&self) -> &[Theme] {
+        &self.themes
+    }
+
+    pub fn default_theme_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Backend explicitly selected in config
+    pub fn backend(&self) -> Option<BackendKind> {
+        self.backend
+    }
+
+    /// Checks whether components referenced by themes are installed
+    pub fn validate(&self, installed: &InstalledThemes, backend: BackendKind) -> Vec<ValidationIssue> {
+        self.themes
+            .iter()
+            .flat_map(|theme| validate_theme(theme, installed, backend))
+            .collect()
+    }
+
+    pub fn templates(&self) -> &[Template] {
+        &self.templates
+    }
+
+    pub fn toggle_pair(&self) -> Option<&TogglePair> {
+        self.toggle.as_ref()
+    }
+
+    /// Returns the closest moment after `date` at which any of the theme spans starts or ends
+    pub fn next_span_boundary(
+        &self,
+        date: chrono::DateTime<chrono::Local>,
+    ) -> Option<chrono::DateTime<chrono::Local>> {
+        self.themes
+            .iter()
+            .filter_map(|theme| theme.span.as_ref())
+            .flat_map(|span| [span.start(), span.stop()])
+            .filter_map(|timespec| timespec.next_occurrence(date))
+            .min()
+    }
+}
+
+/// Checks whether components referenced by given theme are installed
+pub fn validate_theme(
+    theme: &Theme,
+    installed: &InstalledThemes,
+    backend: BackendKind,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (component, value) in theme.spec.components() {
+        if let Some(available) = installed.for_component(&component, backend) {
+            let name = if component.is_font() {
+                discovery::font_family(&value)
+            } else {
+                &value
+            };
+            if !available.contains(name) {
+                issues.push(ValidationIssue {
+                    theme: theme.name.clone(),
+                    component,
+                    value,
+                });
+            }
+        }
+    }
+    issues
 }
 
 impl TryFrom<PathBuf> for Config {
@@ -78,9 +182,9 @@ impl TryFrom<PathBuf> for Config {
     }
 }
 
-pub fn default_path() -> Option<PathBuf> {
-    // We look for $HOME/.config/theme-manager/config.json file
-    if let Some(user_config_dir) = dirs::config_dir() {
+pub fn default_path(bus: &SessionBus) -> Option<PathBuf> {
+    // We look for $HOME/.config/theme-manager/config.json file of the session owner
+    if let Some(user_config_dir) = bus.config_dir() {
         let app_config_path = user_config_dir.join(ConstantRepo::app_name()).join("config.json");
 
         if app_config_path.is_file() {
@@ -90,7 +194,7 @@ pub fn default_path() -> Option<PathBuf> {
     None
 }
 
-pub fn load_config(args: &crate::cli::Args) -> Result<Config> {
+pub fn load_config(args: &crate::cli::Args, bus: &SessionBus) -> Result<Config> {
     // First we check wheter user specified path to a config
     if let Some(config_path) = args.config.clone() {
         match Config::try_from(config_path) {
@@ -103,7 +207,7 @@ pub fn load_config(args: &crate::cli::Args) -> Result<Config> {
 
     info!("Attempting to load configuration from default location");
 
-    if let Some(config_path) = default_path() {
+    if let Some(config_path) = default_path(bus) {
         info!("Config file found in: {:?}. Loading...", config_path);
         match Config::try_from(config_path) {
             Ok(config) => Ok(config),
@@ -11,12 +11,3 @@ impl Context {
         Self { data }
     }
 }
-
-#[allow(clippy::derivable_impls)]
-impl Default for Context {
-    fn default() -> Self {
-        Self {
-            data: DataRepo::default(),
-        }
-    }
-}
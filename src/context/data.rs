@@ -1,20 +1,47 @@
 use std::path::PathBuf;
 
+use chrono::{DateTime, Local};
 use log::{debug, error, info, trace, warn};
+use serde::{Deserialize, Serialize};
 
-use crate::constant::ConstantRepo;
+use crate::{bus::SessionBus, constant::ConstantRepo};
+
+/// Contents of the theme lock file
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ThemeLock {
+    pub theme: String,
+    /// RFC 3339 timestamp after which the lock no longer applies. Lock never expires if not set.
+    pub until: Option<String>,
+}
+
+impl ThemeLock {
+    pub fn is_expired(&self, now: DateTime<Local>) -> bool {
+        match &self.until {
+            Some(until) => match DateTime::parse_from_rfc3339(until) {
+                Ok(until) => now >= until,
+                Err(err) => {
+                    warn!("Failed to parse theme lock expiry date {until}. Reported error: {err}");
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+}
 
 pub struct DataRepo {
     pub app_data_dir: PathBuf,
     pub theme_lock_file: PathBuf,
+    /// Session the data belongs to, files are written on behalf of its owner
+    bus: SessionBus,
 }
 
 impl DataRepo {
-    pub fn new() -> Self {
-        let app_data_dir = DataRepo::default_data_dir().unwrap();
+    pub fn new(bus: SessionBus) -> Self {
+        let app_data_dir = DataRepo::default_data_dir(&bus).unwrap();
         if !app_data_dir.is_dir() {
             warn!("Data directory at {app_data_dir:?} is missing. Attempting to create.");
-            if let Err(err) = std::fs::create_dir_all(&app_data_dir) {
+            if let Err(err) = bus.create_dir_all(&app_data_dir) {
                 error!("Failed to create data directory at {app_data_dir:?}. Reported error: {err}");
             }
         }
@@ -25,33 +52,51 @@ impl DataRepo {
         Self {
             app_data_dir,
             theme_lock_file,
+            bus,
         }
     }
 
-    pub fn default_data_dir() -> Option<PathBuf> {
-        Some(dirs::data_dir().unwrap().join(ConstantRepo::app_name()))
+    pub fn default_data_dir(bus: &SessionBus) -> Option<PathBuf> {
+        Some(bus.data_dir()?.join(ConstantRepo::app_name()))
     }
 
-    pub fn lock_theme(&self, _theme: &str) -> std::io::Result<()> {
+    pub fn lock_theme(&self, theme: &str, until: Option<DateTime<Local>>) -> std::io::Result<()> {
         trace!("Creating theme lock");
-        if self.theme_lock_file.is_file() {
-            return Ok(());
-        }
 
-        match std::fs::File::options()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(&self.theme_lock_file)
+        let lock = ThemeLock {
+            theme: theme.to_owned(),
+            until: until.map(|date| date.to_rfc3339()),
+        };
+
+        match self
+            .bus
+            .write_file(&self.theme_lock_file, serde_json::to_string(&lock)?)
         {
             Ok(_) => Ok(()),
             Err(err) => {
                 error!("Failed to create theme lock file. Error: {}", err);
-                Err(err)
+                Err(std::io::Error::other(err))
             }
         }
     }
 
+    /// Reads theme lock. Lock files with no (or malformed) content are treated as a lock on unknown theme
+    /// with no expiry date.
+    pub fn theme_lock(&self) -> Option<ThemeLock> {
+        if !self.theme_lock_file.is_file() {
+            return None;
+        }
+
+        let lock = std::fs::read_to_string(&self.theme_lock_file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| ThemeLock {
+                theme: String::from("unknown"),
+                until: None,
+            });
+        Some(lock)
+    }
+
     pub fn unlock_theme(&self) -> std::io::Result<()> {
         trace!("Removing theme lock");
 
@@ -70,9 +115,3 @@ impl DataRepo {
         Ok(())
     }
 }
-
-impl Default for DataRepo {
-    fn default() -> Self {
-        Self::new()
-    }
-}
@@ -0,0 +1,224 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use log::{debug, trace};
+use serde::Serialize;
+
+use crate::{backend::BackendKind, bus::SessionBus, theme::Component, util};
+
+/// Names of themes, icon sets and cursors installed in the system, grouped by what they provide
+#[derive(Serialize, Debug, Default)]
+pub struct InstalledThemes {
+    pub gtk3: BTreeSet<String>,
+    pub gtk4: BTreeSet<String>,
+    pub cinnamon: BTreeSet<String>,
+    pub metacity: BTreeSet<String>,
+    pub gnome_shell: BTreeSet<String>,
+    pub xfwm4: BTreeSet<String>,
+    pub icons: BTreeSet<String>,
+    pub cursors: BTreeSet<String>,
+    /// Font families known to fontconfig
+    pub fonts: BTreeSet<String>,
+}
+
+impl InstalledThemes {
+    /// Scans well known theme & icon directories, including the ones in home directory of the session owner
+    pub fn scan(bus: &SessionBus) -> Self {
+        let mut installed = InstalledThemes::default();
+
+        for dir in theme_dirs(bus) {
+            for (name, path) in subdirectories(&dir) {
+                installed.classify_theme(name, &path);
+            }
+        }
+
+        for dir in icon_dirs(bus) {
+            for (name, path) in subdirectories(&dir) {
+                installed.classify_icons(name, &path);
+            }
+        }
+
+        installed.fonts = font_families(bus);
+
+        debug!("Discovered installed themes: {:?}", installed);
+        installed
+    }
+
+    /// Returns names that are valid values of given component, if these can be discovered
+    pub fn for_component(&self, component: &Component, backend: BackendKind) -> Option<&BTreeSet<String>> {
+        match (component, backend) {
+            (Component::Desktop, BackendKind::Cinnamon) => Some(&self.cinnamon),
+            (Component::Desktop, BackendKind::Gnome) => Some(&self.gnome_shell),
+            (Component::Borders, BackendKind::Xfce) => Some(&self.xfwm4),
+            // Plasma color schemes, decorations & look-and-feel packages live elsewhere
+            (Component::Controls | Component::Borders, BackendKind::Kde) => None,
+            (Component::Borders, BackendKind::Wlroots | BackendKind::X11) => None,
+            (Component::Controls, _) => Some(&self.gtk3),
+            (Component::Borders, _) => Some(&self.metacity),
+            (Component::Icons, _) => Some(&self.icons),
+            (Component::Mouse, _) => Some(&self.cursors),
+            // Empty set means fontconfig is not available, rather than no fonts being installed
+            (component, _) if component.is_font() && !self.fonts.is_empty() => Some(&self.fonts),
+            _ => None,
+        }
+    }
+
+    fn classify_theme(&mut self, name: String, path: &Path) {
+        if path.join("gtk-3.0").is_dir() {
+            self.gtk3.insert(name.clone());
+        }
+        if path.join("gtk-4.0").is_dir() {
+            self.gtk4.insert(name.clone());
+        }
+        if path.join("cinnamon").is_dir() {
+            self.cinnamon.insert(name.clone());
+        }
+        if path.join("metacity-1").is_dir() {
+            self.metacity.insert(name.clone());
+        }
+        if path.join("gnome-shell").is_dir() {
+            self.gnome_shell.insert(name.clone());
+        }
+        if path.join("xfwm4").is_dir() {
+            self.xfwm4.insert(name);
+        }
+    }
+
+    fn classify_icons(&mut self, name: String, path: &Path) {
+        if path.join("cursors").is_dir() {
+            self.cursors.insert(name.clone());
+        }
+
+        // Cursor-only themes ship index.theme as well, but without any icon directories listed
+        if let Ok(index) = std::fs::read_to_string(path.join("index.theme")) {
+            if index
+                .lines()
+                .any(|line| line.trim_start().starts_with("Directories"))
+            {
+                self.icons.insert(name);
+            }
+        }
+    }
+}
+
+/// Extracts family name from Pango font description, e.g. `Ubuntu Mono Bold 11` gives `Ubuntu Mono`
+pub fn font_family(description: &str) -> &str {
+    const STYLE_WORDS: &[&str] = &[
+        "Normal",
+        "Regular",
+        "Roman",
+        "Italic",
+        "Oblique",
+        "Thin",
+        "Ultra-Light",
+        "Extra-Light",
+        "Light",
+        "Semi-Light",
+        "Book",
+        "Medium",
+        "Semi-Bold",
+        "Demi-Bold",
+        "Bold",
+        "Ultra-Bold",
+        "Extra-Bold",
+        "Heavy",
+        "Black",
+        "Ultra-Heavy",
+        "Small-Caps",
+        "Condensed",
+        "Expanded",
+        "Semi-Condensed",
+        "Semi-Expanded",
+    ];
+
+    let mut family = description.trim().trim_end_matches(',');
+    while let Some((rest, word)) = family.rsplit_once(' ') {
+        let is_size = word.trim_end_matches("px").parse::<f64>().is_ok();
+        let is_style = STYLE_WORDS.iter().any(|style| style.eq_ignore_ascii_case(word));
+        if !is_size && !is_style {
+            break;
+        }
+        family = rest.trim_end().trim_end_matches(',');
+    }
+    family
+}
+
+/// Lists font families with fc-list. Fonts can have several (e.g. localized) family names, all are listed.
+fn font_families(bus: &SessionBus) -> BTreeSet<String> {
+    if util::find_executable("fc-list").is_none() {
+        debug!("fc-list not found, skipping font discovery");
+        return BTreeSet::new();
+    }
+
+    // Run as the session owner, so that fonts installed in their home directory are listed as well
+    let output = bus
+        .user_command("fc-list")
+        .arg(":")
+        .arg("family")
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .flat_map(|line| line.split(','))
+            .map(|family| family.trim().replace('\\', ""))
+            .filter(|family| !family.is_empty())
+            .collect(),
+        Ok(output) => {
+            debug!("fc-list failed with status: {}", output.status);
+            BTreeSet::new()
+        }
+        Err(err) => {
+            debug!("Failed to run fc-list: {}", err);
+            BTreeSet::new()
+        }
+    }
+}
+
+fn theme_dirs(bus: &SessionBus) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("/usr/share/themes")];
+    if let Some(home) = bus.home_dir() {
+        dirs.push(home.join(".themes"));
+    }
+    if let Some(data) = bus.data_dir() {
+        dirs.push(data.join("themes"));
+    }
+    dirs
+}
+
+fn icon_dirs(bus: &SessionBus) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("/usr/share/icons")];
+    if let Some(home) = bus.home_dir() {
+        dirs.push(home.join(".icons"));
+    }
+    if let Some(data) = bus.data_dir() {
+        dirs.push(data.join("icons"));
+    }
+    dirs
+}
+
+/// Returns (name, path) pairs of directories (or symlinks to directories) found in `dir`
+fn subdirectories(dir: &Path) -> Vec<(String, PathBuf)> {
+    trace!("Scanning {:?}", dir);
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            trace!("Skipping {:?}: {}", dir, err);
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_owned();
+            Some((name, path))
+        })
+        .collect()
+}
@@ -0,0 +1,328 @@
+use std::{collections::BTreeSet, os::unix::fs::FileTypeExt, path::Path};
+
+use crate::{
+    backend::{Backend, BackendKind, Selection},
+    bus::SessionBus,
+    config::Config,
+    context::data::DataRepo,
+    discovery::InstalledThemes,
+    gsettings::GSettings,
+    gtk4, integration, util,
+};
+
+/// Result of a single environment check
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+    pub remedy: Option<String>,
+}
+
+impl Check {
+    pub fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            passed: true,
+            detail: detail.into(),
+            remedy: None,
+        }
+    }
+
+    pub fn fail(name: impl Into<String>, detail: impl Into<String>, remedy: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            passed: false,
+            detail: detail.into(),
+            remedy: Some(remedy.into()),
+        }
+    }
+}
+
+pub fn check_session_bus(bus: &SessionBus) -> Check {
+    let address = bus.address();
+    let name = format!("Session bus (found in {})", bus.origin());
+
+    match address.strip_prefix("unix:path=") {
+        Some(path) => {
+            // Address may carry additional comma separated parameters, e.g. guid
+            let path = Path::new(path.split(',').next().unwrap_or(path));
+            match path.metadata() {
+                Ok(meta) if meta.file_type().is_socket() => {
+                    Check::pass(name, format!("socket {} exists", path.display()))
+                }
+                Ok(_) => Check::fail(
+                    name,
+                    format!("{} is not a socket", path.display()),
+                    "make sure the session bus is running for the user the tool is run as",
+                ),
+                Err(err) => Check::fail(
+                    name,
+                    format!("socket {} is not accessible: {}", path.display(), err),
+                    "run the tool as the user owning the graphical session",
+                ),
+            }
+        }
+        // Abstract sockets & other transports can not be checked without connecting to the bus
+        None => Check::pass(name, format!("using address {}", address)),
+    }
+}
+
+pub fn check_backend(selection: &Selection) -> Check {
+    let name = format!("Backend {}", selection.kind);
+    if selection.fallback {
+        Check::fail(
+            name,
+            selection.reason.clone(),
+            "set \"backend\" in config or pass --backend",
+        )
+    } else {
+        Check::pass(name, selection.reason.clone())
+    }
+}
+
+pub fn check_binary(binary: &str, remedy: &str) -> Check {
+    let name = format!("Binary {}", binary);
+    match util::find_executable(binary) {
+        Some(path) => Check::pass(name, format!("found at {}", path.display())),
+        None => Check::fail(name, "not found in PATH", remedy),
+    }
+}
+
+pub fn check_schema_keys(gset: &GSettings, keys: &[(&str, &str)]) -> Vec<Check> {
+    keys.iter()
+        .map(|(schema, key)| {
+            let name = format!("GSettings key {} {}", schema, key);
+            if gset.has_key(schema, key) {
+                Check::pass(name, "present")
+            } else {
+                Check::fail(
+                    name,
+                    "schema or key is not installed",
+                    format!("install the package providing {} schema", schema),
+                )
+            }
+        })
+        .collect()
+}
+
+pub fn check_writable_dir(name: &str, dir: &Path) -> Check {
+    if !dir.is_dir() {
+        Check::fail(
+            name,
+            format!("{} does not exist", dir.display()),
+            format!("create it with: mkdir -p {}", dir.display()),
+        )
+    } else if util::is_writable(dir) {
+        Check::pass(name, format!("{} is writable", dir.display()))
+    } else {
+        Check::fail(
+            name,
+            format!("{} is not writable", dir.display()),
+            "fix the directory ownership or permissions",
+        )
+    }
+}
+
+pub fn check_config(cfg: &Config, installed: &InstalledThemes, backend: BackendKind) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    for theme in cfg.themes() {
+        let wallpaper = theme.spec.wallpaper.to_string_lossy();
+        let path = Path::new(wallpaper.strip_prefix("file://").unwrap_or(&wallpaper));
+        let name = format!("Wallpaper of theme {}", theme.name);
+        if path.is_file() {
+            checks.push(Check::pass(name, format!("{} exists", path.display())));
+        } else {
+            checks.push(Check::fail(
+                name,
+                format!("{} does not exist", path.display()),
+                "fix the wallpaper path in config",
+            ));
+        }
+    }
+
+    let issues = cfg.validate(installed, backend);
+    if issues.is_empty() {
+        checks.push(Check::pass(
+            "Theme components",
+            "all referenced themes are installed",
+        ));
+    }
+    for issue in issues {
+        checks.push(Check::fail(
+            "Theme components",
+            issue.to_string(),
+            "install the theme or fix its name in config - see `available` command",
+        ));
+    }
+
+    checks
+}
+
+/// Validates values of extra GSettings keys against ranges declared in their schemas
+pub fn check_gsettings_entries(cfg: &Config, gset: &GSettings) -> Vec<Check> {
+    cfg.themes()
+        .iter()
+        .flat_map(|theme| theme.spec.gsettings.iter().map(move |entry| (theme, entry)))
+        .map(|(theme, entry)| {
+            let name = format!(
+                "GSettings key {} {} of theme {}",
+                entry.schema, entry.key, theme.name
+            );
+            match gset.validate(&entry.schema, &entry.key, &entry.value) {
+                Ok(()) => Check::pass(name, format!("{} is valid", entry.value)),
+                Err(err) => Check::fail(
+                    name,
+                    err,
+                    format!(
+                        "check accepted values with: gsettings range {} {}",
+                        entry.schema, entry.key
+                    ),
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Checks that applications referenced by themes are supported & themes of theirs are available
+pub fn check_apps(cfg: &Config, bus: &SessionBus) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut checked_apps = BTreeSet::new();
+
+    for theme in cfg.themes() {
+        for (app, app_theme) in &theme.spec.apps {
+            let integration = match integration::create(app, bus) {
+                Ok(integration) => integration,
+                Err(err) => {
+                    checks.push(Check::fail(
+                        format!("Application {} of theme {}", app, theme.name),
+                        err,
+                        "fix the application name in `apps` of the theme",
+                    ));
+                    continue;
+                }
+            };
+
+            if checked_apps.insert(app.as_str()) {
+                checks.extend(integration.checks());
+            }
+
+            let name = format!("Application {} of theme {}", integration.name(), theme.name);
+            match integration.validate(app_theme) {
+                Ok(()) => checks.push(Check::pass(name, format!("{} is available", app_theme))),
+                Err(err) => checks.push(Check::fail(
+                    name,
+                    err,
+                    "install the theme or fix its name in config",
+                )),
+            }
+        }
+    }
+
+    checks
+}
+
+/// Checks that GTK4 css sources of themes exist
+pub fn check_gtk4_css(cfg: &Config, bus: &SessionBus) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    for theme in cfg.themes() {
+        if let Some(source) = &theme.spec.gtk4_css {
+            let name = format!("GTK4 css of theme {}", theme.name);
+            match gtk4::validate(bus, source) {
+                Ok(source) => checks.push(Check::pass(name, format!("{} exists", source.display()))),
+                Err(err) => checks.push(Check::fail(name, err, "fix the gtk4_css path in config")),
+            }
+        }
+    }
+
+    checks
+}
+
+/// Checks that templates render with palette of every theme
+pub fn check_templates(cfg: &Config, bus: &SessionBus) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    for template in cfg.templates() {
+        for theme in cfg.themes() {
+            let name = format!("Template {} of theme {}", template.source, theme.name);
+            match template.render(bus, theme) {
+                Ok(_) => checks.push(Check::pass(name, "renders")),
+                Err(err) => checks.push(Check::fail(
+                    name,
+                    err,
+                    "fix the template or define missing colors in `palette` of the theme",
+                )),
+            }
+        }
+    }
+
+    checks
+}
+
+pub fn run_checks(
+    cfg: Option<&Config>,
+    config_dir: Option<&Path>,
+    data: &DataRepo,
+    selection: &Selection,
+    backend: &dyn Backend,
+) -> Vec<Check> {
+    let mut checks = vec![check_session_bus(backend.session_bus()), check_backend(selection)];
+    checks.extend(backend.checks());
+
+    if let Some(cfg) = cfg {
+        checks.extend(check_apps(cfg, backend.session_bus()));
+        checks.extend(check_gtk4_css(cfg, backend.session_bus()));
+        checks.extend(check_templates(cfg, backend.session_bus()));
+    }
+
+    let uses_fonts = cfg.is_some_and(|cfg| {
+        cfg.themes().iter().any(|theme| {
+            theme
+                .spec
+                .components()
+                .iter()
+                .any(|(component, _)| component.is_font())
+        })
+    });
+    if uses_fonts {
+        checks.push(check_binary(
+            "fc-list",
+            "install fontconfig to check whether configured fonts are installed",
+        ));
+    }
+
+    if let Some(config_dir) = config_dir {
+        checks.push(check_writable_dir("Config directory", config_dir));
+    }
+    checks.push(check_writable_dir("Data directory", &data.app_data_dir));
+
+    if let Some(cfg) = cfg {
+        checks.extend(check_config(
+            cfg,
+            &InstalledThemes::scan(backend.session_bus()),
+            backend.kind(),
+        ));
+
+        let uses_gsettings = cfg.themes().iter().any(|theme| !theme.spec.gsettings.is_empty());
+        if uses_gsettings && util::find_executable("gsettings").is_some() {
+            checks.extend(check_gsettings_entries(
+                cfg,
+                &GSettings::new(backend.session_bus().clone()),
+            ));
+        }
+    }
+
+    checks
+}
+
+pub fn print_report(checks: &[Check]) {
+    for check in checks {
+        let status = if check.passed { "PASS" } else { "FAIL" };
+        println!("[{}] {}: {}", status, check.name, check.detail);
+        if let Some(remedy) = &check.remedy {
+            println!("       remedy: {}", remedy);
+        }
+    }
+}
@@ -1,346 +1,165 @@
-use libc::geteuid;
-use log::{debug, error, info};
-use std::{
-    borrow::Borrow, path::PathBuf, process::{Command, ExitStatus, Output, Stdio}
-};
+use log::debug;
+use std::process::Stdio;
 
-use crate::theme::{Theme, ThemeSpec};
-
-const DBUS_SESSION_BUS_ADDRESS_KEY: &str = "DBUS_SESSION_BUS_ADDRESS";
-const ORG_CINNAMON_DESKTOP_INTERFACE_SCHEMA: &str = "org.cinnamon.desktop.interface";
-const ORG_GNOME_DESKTOP_INTERFACE_SCHEMA: &str = "org.gnome.desktop.interface";
-
-/// Handles command result / command failure
-fn handle_result(result: Result<ExitStatus, std::io::Error>, success_msg: String, failure_msg: String) {
-    match result {
-        Ok(status) => {
-            if status.success() {
-                info!("{}", success_msg);
-            } else if let Some(ret_code) = status.code() {
-                error!(
-                    "{}. Process returned non-zero return code: {}",
-                    failure_msg, ret_code
-                );
-            } else {
-                error!("{}. Process was most likely interrupted", failure_msg);
-            }
-        }
-        Err(err) => {
-            error!("Failed to execute the process with error: {}", err);
-        }
-    }
-}
-
-fn handle_get_result(result: std::io::Result<Output>) -> Result<String, String> {
-    match result {
-        Ok(output) => match String::from_utf8(output.stdout) {
-            Ok(stdout) => Ok(stdout.replace('\'', "").trim().to_owned()),
-            Err(err) => Err(err.to_string()),
-        },
-        Err(err) => Err(err.to_string()),
-    }
-}
+use crate::{bus::SessionBus, util};
 
+/// Thin wrapper around `gsettings` binary, run against the session bus
+#[derive(Clone)]
 pub struct GSettings {
-    dbus_session_bus_address: String,
+    bus: SessionBus,
 }
 
 impl GSettings {
-    pub fn new() -> Self {
-        // Can I check somehow whether this call failed?
-        // Or does in not fail?
-        let euid = unsafe { geteuid() };
-        debug!("Creating Gsettings insance with euid: {}", euid);
-        GSettings {
-            dbus_session_bus_address: format!("unix:path=/run/user/{}/bus", euid),
-        }
-    }
-
-    fn set_desktop(&self, theme: &str) {
-        let result = Command::new("gsettings")
-            .arg("set")
-            .arg("org.cinnamon.theme")
-            .arg("name")
-            .arg(theme)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .status();
-
-        handle_result(
-            result,
-            format!("Desktop theme set to: {}", theme),
-            format!("Failed to set desktop theme to: {}", theme),
-        );
-    }
-
-    fn get_desktop(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
-            .arg("get")
-            .arg("org.cinnamon.theme")
-            .arg("name")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .stdout(Stdio::piped())
-            .output();
-
-        handle_get_result(result)
-    }
-
-    fn set_mouse(&self, theme: &str) {
-        let result = Command::new("gsettings")
-            .arg("set")
-            .arg("org.cinnamon.desktop.interface")
-            .arg("cursor-theme")
-            .arg(theme)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .status();
-
-        handle_result(
-            result,
-            format!("Mouse theme set to: {}", theme),
-            format!("Failed to set mouse theme to: {}", theme),
-        );
-    }
-
-    fn get_mouse(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
-            .arg("get")
-            .arg("org.cinnamon.desktop.interface")
-            .arg("cursor-theme")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .output();
-
-        handle_get_result(result)
-    }
-
-    fn set_controls(&self, theme: &str) {
-        let result = Command::new("gsettings")
-            .arg("set")
-            .arg("org.cinnamon.desktop.interface")
-            .arg("gtk-theme")
-            .arg(theme)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .status();
-
-        handle_result(
-            result,
-            format!("Controls theme set to: {}", theme),
-            format!("Failed to set controls theme to: {}", theme),
-        );
-    }
-
-    fn get_controls(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
-            .arg("get")
-            .arg("org.cinnamon.desktop.interface")
-            .arg("gtk-theme")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .output();
-
-        handle_get_result(result)
-    }
-
-    fn set_icons(&self, theme: &str) {
-        let result = Command::new("gsettings")
-            .arg("set")
-            .arg("org.cinnamon.desktop.interface")
-            .arg("icon-theme")
-            .arg(theme)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .status();
-
-        handle_result(
-            result,
-            format!("Icons theme set to: {}", theme),
-            format!("Failed to set icons theme to: {}", theme),
-        );
-    }
-
-    fn get_icons(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
-            .arg("get")
-            .arg("org.cinnamon.desktop.interface")
-            .arg("icon-theme")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .output();
-
-        handle_get_result(result)
+    pub fn new(bus: SessionBus) -> Self {
+        debug!("Creating Gsettings instance with session bus: {}", bus.address());
+        GSettings { bus }
     }
 
-    fn set_borders(&self, theme: &str) {
-        let result = Command::new("gsettings")
-            .arg("set")
-            .arg("org.cinnamon.desktop.wm.preferences")
-            .arg("theme")
-            .arg(theme)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .status();
-
-        handle_result(
-            result,
-            format!("Borders theme set to: {}", theme),
-            format!("Failed to set borders theme to: {}", theme),
-        );
+    pub fn session_bus(&self) -> &SessionBus {
+        &self.bus
     }
 
-    fn get_borders(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
-            .arg("get")
-            .arg("org.cinnamon.desktop.wm.preferences")
-            .arg("theme")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .output();
-
-        handle_get_result(result)
-    }
-
-    fn set_wallpaper(&self, path: &str) {
-        let mut sanitized_path: String = path.to_owned();
-        if !path.starts_with("file://") {
-            sanitized_path = "file://".to_owned() + path;
-        }
-
-        let result = Command::new("gsettings")
-            .arg("set")
-            .arg("org.cinnamon.desktop.background")
-            .arg("picture-uri")
-            .arg(sanitized_path)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
+    /// Checks whether given key is present in installed schema
+    pub fn has_key(&self, schema: &str, key: &str) -> bool {
+        let result = self
+            .bus
+            .command("gsettings")
+            .arg("writable")
+            .arg(schema)
+            .arg(key)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
             .status();
 
-        handle_result(
-            result,
-            format!("Wallpaper set to: {}", path),
-            format!("Failed to set wallpaper to: {}", path),
-        );
+        matches!(result, Ok(status) if status.success())
     }
 
-    fn get_wallpaper(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
+    pub fn get(&self, schema: &str, key: &str) -> Result<String, String> {
+        let result = self
+            .bus
+            .command("gsettings")
             .arg("get")
-            .arg("org.cinnamon.desktop.background")
-            .arg("picture-uri")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
-            .output();
-
-        handle_get_result(result)
-    }
-
-    fn set_kitty(&self, theme: &str) {
-        let result = Command::new("kitty")
-            .arg("+kitten")
-            .arg("themes")
-            .arg("--reload-in=all")
-            .arg(theme)
-            .status();
-
-        handle_result(
-            result,
-            format!("Kitty theme set to: {}", theme),
-            format!("Failed to set kitty theme to: {}", theme),
-        );
-    }
-
-    fn get_kitty(&self) -> Result<String, String> {
-        let result = Command::new("kitty")
-            .arg("+kitten")
-            .arg("themes")
-            .arg("--dump-theme")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
+            .arg(schema)
+            .arg(key)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
             .output();
 
-        handle_get_result(result)
+        util::handle_get_result(result)
     }
 
-    /// Sets theme preference regarding light / dark mode
-    fn set_theme_preference(&self, color_scheme_preference: &str) {
-
-        let result = Command::new("gsettings")
+    pub fn set(&self, schema: &str, key: &str, value: &str) -> Result<(), String> {
+        let result = self
+            .bus
+            .command("gsettings")
             .arg("set")
-            .arg(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA)
-            .arg("color-scheme")
-            .arg(color_scheme_preference)
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
+            .arg(schema)
+            .arg(key)
+            .arg(value)
             .status();
 
-        handle_result(
-            result,
-            format!("Theme preference set to: {}", color_scheme_preference),
-            format!("Failed to set theme preference to: {}", color_scheme_preference),
-        );
+        util::check_status(result)
     }
 
-    fn get_theme_preference(&self) -> Result<String, String> {
-        let result = Command::new("gsettings")
-            .arg("get")
-            .arg(ORG_GNOME_DESKTOP_INTERFACE_SCHEMA)
-            .arg("color-scheme")
-            .env(DBUS_SESSION_BUS_ADDRESS_KEY, &self.dbus_session_bus_address)
+    /// Retrieves range of values accepted by given key, as printed by `gsettings range`
+    pub fn range(&self, schema: &str, key: &str) -> Result<String, String> {
+        let result = self
+            .bus
+            .command("gsettings")
+            .arg("range")
+            .arg(schema)
+            .arg(key)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
             .output();
 
-        handle_get_result(result)
+        util::handle_get_result(result)
     }
 
-    pub fn set_theme(&self, theme: &Theme) {
-        // First we retrieve current theme
-        let current_theme_spec = self.get_theme();
-
-        if current_theme_spec.desktop != theme.spec.desktop {
-            self.set_desktop(&theme.spec.desktop);
-        }
-
-        if current_theme_spec.mouse != theme.spec.mouse {
-            self.set_mouse(&theme.spec.mouse);
-        }
-
-        if current_theme_spec.controls != theme.spec.controls {
-            self.set_controls(&theme.spec.controls);
-        }
-
-        if current_theme_spec.icons != theme.spec.icons {
-            self.set_icons(&theme.spec.icons);
-        }
-
-        if current_theme_spec.borders != theme.spec.borders {
-            self.set_borders(&theme.spec.borders);
-        }
-
-        if current_theme_spec.wallpaper != theme.spec.wallpaper {
-            self.set_wallpaper(theme.spec.wallpaper.to_str().unwrap());
-        }
+    /// Checks whether value is accepted by given key, so that invalid value is reported before it is set
+    pub fn validate(&self, schema: &str, key: &str, value: &str) -> Result<(), String> {
+        let range = self.range(schema, key)?;
+        let mut lines = range.lines().map(str::trim);
+        let kind = lines.next().unwrap_or_default();
+        let value = normalize(value);
 
-        if current_theme_spec.color_scheme_preference != theme.spec.color_scheme_preference {
-            self.set_theme_preference(theme.spec.color_scheme_preference.borrow());
+        if kind == "enum" {
+            let allowed: Vec<&str> = lines.collect();
+            return if allowed.contains(&value.as_str()) {
+                Ok(())
+            } else {
+                Err(format!("{} is not one of: {}", value, allowed.join(", ")))
+            };
         }
 
-        if let Some(kitty_theme) = &theme.spec.kitty {
-            if let Some(crt_kitty_theme) = current_theme_spec.kitty {
-                if crt_kitty_theme != *kitty_theme {
-                    self.set_kitty(kitty_theme);
+        let parts: Vec<&str> = kind.split_whitespace().collect();
+        match parts.as_slice() {
+            ["range", type_code, min, max] => {
+                check_type(type_code, &value)?;
+                let (number, min, max) = (parse_number(&value)?, parse_number(min)?, parse_number(max)?);
+                if number < min || number > max {
+                    return Err(format!("{} is out of range {} - {}", value, min, max));
                 }
-            } else {
-                self.set_kitty(kitty_theme);
+                Ok(())
             }
+            ["type", type_code] => check_type(type_code, &value),
+            // Flags & other complex types are left for gsettings to validate
+            _ => Ok(()),
         }
     }
+}
+
+/// Strips quotes and GVariant type annotations (e.g. `uint32 5`), so that value given in config can be
+/// compared with the one printed by `gsettings get`
+pub fn normalize(value: &str) -> String {
+    const TYPE_PREFIXES: &[&str] = &[
+        "byte ", "int16 ", "uint16 ", "int32 ", "uint32 ", "int64 ", "uint64 ", "double ",
+    ];
+
+    let value = value.trim();
+    let value = TYPE_PREFIXES
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .unwrap_or(value);
+    value.replace('\'', "")
+}
+
+/// Compares GSettings values ignoring their formatting, e.g. `1.0` equals `1` and `'a'` equals `a`
+pub fn values_equal(left: &str, right: &str) -> bool {
+    let (left, right) = (normalize(left), normalize(right));
+    match (left.parse::<f64>(), right.parse::<f64>()) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => left == right,
+    }
+}
 
-    pub fn get_theme(&self) -> ThemeSpec {
-        let desktop = self.get_desktop().unwrap_or_else(|err| err);
-        let mouse = self.get_mouse().unwrap_or_else(|err| err);
-        let controls = self.get_controls().unwrap_or_else(|err| err);
-        let icons = self.get_icons().unwrap_or_else(|err| err);
-        let borders = self.get_borders().unwrap_or_else(|err| err);
-        let wallpaper = PathBuf::from(self.get_wallpaper().unwrap_or_else(|err| err));
-        let kitty_theme = self.get_kitty().unwrap_or_else(|err| err);
-        let color_scheme_preference = self.get_theme_preference().unwrap_or_else(|err| err);
+fn parse_number(value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .map_err(|_| format!("{} is not a number", value))
+}
 
-        ThemeSpec {
-            desktop,
-            mouse,
-            controls,
-            icons,
-            borders,
-            wallpaper,
-            kitty: Some(kitty_theme),
-            color_scheme_preference,
-        }
+/// Checks value against GVariant basic type code
+fn check_type(type_code: &str, value: &str) -> Result<(), String> {
+    let valid = match type_code {
+        "b" => value == "true" || value == "false",
+        "y" => value.parse::<u8>().is_ok(),
+        "n" => value.parse::<i16>().is_ok(),
+        "q" => value.parse::<u16>().is_ok(),
+        "i" | "h" => value.parse::<i32>().is_ok(),
+        "u" => value.parse::<u32>().is_ok(),
+        "x" => value.parse::<i64>().is_ok(),
+        "t" => value.parse::<u64>().is_ok(),
+        "d" => value.parse::<f64>().is_ok(),
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not a valid value of GVariant type {}",
+            value, type_code
+        ))
     }
 }
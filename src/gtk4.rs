@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use log::{error, info, trace, warn};
+
+use crate::{bus::SessionBus, constant::ConstantRepo, integration};
+
+/// Entries of gtk-4.0 directory linked from the theme
+const ENTRIES: &[&str] = &["gtk.css", "gtk-dark.css", "assets"];
+/// Records source of currently linked entries, entries are only considered managed if it exists
+const STATE_FILE: &str = ".theme-manager";
+
+/// libadwaita apps ignore GTK theme setting, custom look is only possible through user gtk.css. Theme's
+/// gtk.css (along with gtk-dark.css & assets, if present) is symlinked into ~/.config/gtk-4.0.
+struct Gtk4Css {
+    bus: SessionBus,
+    dir: PathBuf,
+}
+
+impl Gtk4Css {
+    fn new(bus: &SessionBus) -> Result<Self, String> {
+        bus.config_dir()
+            .map(|dir| Gtk4Css {
+                bus: bus.clone(),
+                dir: dir.join("gtk-4.0"),
+            })
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())
+    }
+
+    /// Resolves source given in config to the absolute path links point to (relative link targets would be
+    /// resolved against ~/.config/gtk-4.0). Leading `~` is expanded, relative paths are resolved against
+    /// ~/.config/theme-manager.
+    fn resolve(&self, source: &Path) -> Result<PathBuf, String> {
+        let base = self
+            .bus
+            .config_dir()
+            .map(|dir| dir.join(ConstantRepo::app_name()))
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())?;
+        integration::resolve_path(&self.bus, &base, &source.to_string_lossy())?
+            .canonicalize()
+            .map_err(|err| format!("{} does not exist: {}", source.display(), err))
+    }
+
+    /// Source currently linked, if any
+    fn current(&self) -> Option<PathBuf> {
+        std::fs::read_to_string(self.dir.join(STATE_FILE))
+            .ok()
+            .map(|source| PathBuf::from(source.trim()))
+    }
+
+    /// Entries to link, as (name, source path) pairs. Source is either theme's gtk-4.0 directory or a single
+    /// css file.
+    fn entries_of(source: &Path) -> Result<Vec<(&'static str, PathBuf)>, String> {
+        if source.is_file() {
+            return Ok(vec![("gtk.css", source.to_owned())]);
+        }
+        if !source.join("gtk.css").is_file() {
+            return Err(format!("{} contains no gtk.css", source.display()));
+        }
+        Ok(ENTRIES
+            .iter()
+            .map(|name| (*name, source.join(name)))
+            .filter(|(_, path)| path.exists())
+            .collect())
+    }
+
+    fn is_intact(&self, source: &Path) -> bool {
+        Gtk4Css::entries_of(source).is_ok_and(|entries| {
+            entries
+                .iter()
+                .all(|(name, path)| std::fs::read_link(self.dir.join(name)).is_ok_and(|link| link == *path))
+        })
+    }
+
+    /// Removes links created for the current source. Links pointing elsewhere were made by the user and are
+    /// left alone.
+    fn remove_managed(&self) -> Result<(), String> {
+        let current = match self.current() {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+        for name in ENTRIES {
+            let path = self.dir.join(name);
+            // Single css file source is linked as gtk.css itself
+            let is_managed =
+                std::fs::read_link(&path).is_ok_and(|link| link == current || link == current.join(name));
+            if is_managed {
+                std::fs::remove_file(&path)
+                    .map_err(|err| format!("Failed to remove {}: {}", path.display(), err))?;
+            }
+        }
+        let state = self.dir.join(STATE_FILE);
+        std::fs::remove_file(&state).map_err(|err| format!("Failed to remove {}: {}", state.display(), err))
+    }
+
+    /// Moves aside file the user placed there, so that it is not lost
+    fn backup(path: &Path) -> Result<(), String> {
+        let backup = PathBuf::from(format!(
+            "{}.backup-{}",
+            path.display(),
+            Local::now().format("%Y%m%d%H%M%S")
+        ));
+        std::fs::rename(path, &backup)
+            .map_err(|err| format!("Failed to back up {}: {}", path.display(), err))?;
+        warn!("Existing {} moved to {}", path.display(), backup.display());
+        Ok(())
+    }
+
+    fn link(&self, source: &Path) -> Result<(), String> {
+        let entries = Gtk4Css::entries_of(source)?;
+        self.bus.create_dir_all(&self.dir)?;
+
+        for (name, path) in entries {
+            let target = self.dir.join(name);
+            if target.symlink_metadata().is_ok() {
+                Gtk4Css::backup(&target)?;
+            }
+            self.bus.symlink(&path, &target)?;
+        }
+
+        self.bus
+            .write_file(&self.dir.join(STATE_FILE), source.to_string_lossy().as_bytes())
+    }
+
+    fn apply(&self, source: Option<&Path>) -> Result<(), String> {
+        // Source is checked before any of the existing files is touched
+        let source = source.map(|source| self.resolve(source)).transpose()?;
+        let source = source.as_deref();
+        let current = self.current();
+        if current.as_deref() == source && source.is_none_or(|source| self.is_intact(source)) {
+            trace!("GTK4 css already set to: {:?}", source);
+            return Ok(());
+        }
+
+        self.remove_managed()?;
+        match source {
+            Some(source) => {
+                self.link(source)?;
+                info!("GTK4 css set to: {}", source.display());
+            }
+            None => info!("GTK4 css removed"),
+        }
+        Ok(())
+    }
+}
+
+/// Links theme's gtk.css into place, or removes previously linked one if theme has none
+pub fn apply(bus: &SessionBus, source: Option<&Path>) {
+    let result = Gtk4Css::new(bus).and_then(|gtk4| gtk4.apply(source));
+    if let Err(err) = result {
+        error!("Failed to set GTK4 css. {}", err);
+    }
+}
+
+/// Source of currently linked gtk.css
+pub fn current(bus: &SessionBus) -> Option<PathBuf> {
+    Gtk4Css::new(bus).ok()?.current()
+}
+
+/// Checks whether given source can be linked, returns the resolved source
+pub fn validate(bus: &SessionBus, source: &Path) -> Result<PathBuf, String> {
+    let gtk4 = Gtk4Css::new(bus)?;
+    let source = gtk4.resolve(source)?;
+    Gtk4Css::entries_of(&source).map(|_| source)
+}
@@ -1,27 +1,74 @@
 use std::{
     borrow::Borrow,
+    collections::BTreeSet,
     path::{Path, PathBuf},
-    process::Command,
 };
 
 use chrono::Local;
 use log::{error, info, trace, warn};
+use serde::Serialize;
 
-use crate::{cli::Args, command::Commands, config::Config, context::Context, gsettings::GSettings};
+use crate::{
+    backend::{self, Backend, Selection},
+    bus::SessionBus,
+    cli::Args,
+    command::Commands,
+    config::{self, Config},
+    constant::ConstantRepo,
+    context::{data::DataRepo, Context},
+    discovery::InstalledThemes,
+    doctor, gtk4, integration, template,
+    theme::{Component, GSettingsEntry, Theme},
+};
+
+pub fn handle_cmd(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    // Config, data & themes are looked up in home directory of the session owner. Bus address itself is only
+    // discovered once a command talks to the session.
+    let bus = SessionBus::resolve(args.user.as_deref())?;
+    bus.act_as_owner()?;
+    let mut ctx = Context::new(DataRepo::new(bus.clone()));
+    let ctx = &mut ctx;
+    let load_config = || config::load_config(&args, &bus);
 
-pub fn handle_cmd(ctx: &mut Context, args: Args, cfg: Config) -> Result<(), Box<dyn std::error::Error>> {
-    let gsettings = GSettings::new();
-    match args.command {
-        Commands::Set { name } => handle_set_cmd(ctx, name, cfg, &gsettings),
-        Commands::Get => handle_get_cmd(ctx, &gsettings),
-        Commands::Edit { editor } => handle_edit_cmd(ctx, editor, args.config),
-        Commands::Lock => handle_lock_cmd(ctx),
+    match args.command.clone() {
+        Commands::Set { name } => {
+            let cfg = load_config()?;
+            let (_, backend) = create_backend(&args, &cfg, &bus);
+            handle_set_cmd(ctx, name, cfg, backend.as_ref())
+        }
+        Commands::Get => {
+            let cfg = load_config()?;
+            let (selection, backend) = create_backend(&args, &cfg, &bus);
+            handle_get_cmd(ctx, &selection, cfg, backend.as_ref())
+        }
+        Commands::Edit { editor } => handle_edit_cmd(ctx, editor, args.config.clone(), &bus),
+        Commands::Lock => {
+            let cfg = load_config()?;
+            let (_, backend) = create_backend(&args, &cfg, &bus);
+            handle_lock_cmd(ctx, cfg, backend.as_ref())
+        }
         Commands::Unlock => handle_unlock_cmd(ctx),
+        Commands::List { json } => handle_list_cmd(ctx, json, load_config()?),
+        Commands::Available { json } => handle_available_cmd(ctx, json, &bus),
+        // Doctor reports config problems itself, so it must not depend on config being loaded successfully
+        Commands::Doctor => return handle_doctor_cmd(ctx, &args, bus),
+        Commands::Toggle { lock } => {
+            let cfg = load_config()?;
+            let (_, backend) = create_backend(&args, &cfg, &bus);
+            handle_toggle_cmd(ctx, lock, cfg, backend.as_ref())
+        }
     }
     Ok(())
 }
 
-fn handle_set_cmd(ctx: &mut Context, theme_name: Option<String>, cfg: Config, gset: &GSettings) {
+fn create_backend(args: &Args, cfg: &Config, bus: &SessionBus) -> (Selection, Box<dyn Backend>) {
+    let selection = Selection::resolve(args.backend, cfg.backend(), bus);
+    info!("Using {} backend ({})", selection.kind, selection.reason);
+    let backend = backend::create(selection.kind, bus.clone());
+    (selection, backend)
+}
+
+fn handle_set_cmd(ctx: &mut Context, theme_name: Option<String>, cfg: Config, backend: &dyn Backend) {
     info!("Running Set command");
 
     // First we check whether user specified a concrete theme
@@ -31,13 +78,15 @@ fn handle_set_cmd(ctx: &mut Context, theme_name: Option<String>, cfg: Config, gs
         // If so, we check wheter theme of given name is present in config file
         // In case such theme does not exist we print error and exit gracefully
         if let Some(theme) = cfg.theme_for_name(&name) {
-            gset.set_theme(theme);
+            warn_about_issues(backend, theme);
+            apply_theme(backend, &cfg, theme);
         } else {
             error!("Failed to find theme for given name: {}", name);
         }
     } else if let Some(theme) = cfg.theme_for_time(Local::now()) {
         if !is_theme_locked(ctx) {
-            gset.set_theme(theme);
+            warn_about_issues(backend, theme);
+            apply_theme(backend, &cfg, theme);
         } else {
             info!("Theme is locked. Do not performing any changes");
         }
@@ -46,19 +95,86 @@ fn handle_set_cmd(ctx: &mut Context, theme_name: Option<String>, cfg: Config, gs
     }
 }
 
-fn handle_get_cmd(_ctx: &mut Context, gset: &GSettings) {
+fn apply_theme(backend: &dyn Backend, cfg: &Config, theme: &Theme) {
+    backend::set_theme(backend, theme);
+    gtk4::apply(backend.session_bus(), theme.spec.gtk4_css.as_deref());
+
+    integration::apply_all(backend.session_bus(), &theme.spec.apps);
+    template::apply_all(backend.session_bus(), cfg.templates(), theme);
+}
+
+/// Names of applications themed by any of the configured themes
+fn configured_apps(cfg: &Config) -> BTreeSet<&str> {
+    cfg.themes()
+        .iter()
+        .flat_map(|theme| theme.spec.apps.keys())
+        .map(String::as_str)
+        .collect()
+}
+
+/// Retrieves current values of all extra GSettings keys referenced in config
+fn current_gsettings(cfg: &Config, backend: &dyn Backend) -> Vec<GSettingsEntry> {
+    let mut entries: Vec<GSettingsEntry> = Vec::new();
+    for entry in cfg.themes().iter().flat_map(|theme| &theme.spec.gsettings) {
+        if entries
+            .iter()
+            .any(|known| known.schema == entry.schema && known.key == entry.key)
+        {
+            continue;
+        }
+        let component = Component::GSettings {
+            schema: entry.schema.clone(),
+            key: entry.key.clone(),
+        };
+        if !backend.supports(&component) {
+            continue;
+        }
+        entries.push(GSettingsEntry {
+            schema: entry.schema.clone(),
+            key: entry.key.clone(),
+            value: backend.get(&component).unwrap_or_else(|err| err),
+        });
+    }
+    entries
+}
+
+/// Warns about theme components that are not installed. Themes that are already applied are not checked, so
+/// that periodic `set` does not scan theme directories & fonts every time.
+fn warn_about_issues(backend: &dyn Backend, theme: &Theme) {
+    if backend::is_theme_applied(backend, theme) {
+        return;
+    }
+    config::validate_theme(
+        theme,
+        &InstalledThemes::scan(backend.session_bus()),
+        backend.kind(),
+    )
+    .iter()
+    .for_each(|issue| warn!("{}", issue));
+}
+
+fn handle_get_cmd(_ctx: &mut Context, selection: &Selection, cfg: Config, backend: &dyn Backend) {
     info!("Running Get command");
-    let theme = gset.get_theme();
+    info!("Backend: {} ({})", selection.kind, selection.reason);
+    let mut theme = backend::get_theme(backend);
+    theme.gsettings = current_gsettings(&cfg, backend);
+    theme.gtk4_css = gtk4::current(backend.session_bus());
+    theme.apps = integration::current_all(backend.session_bus(), configured_apps(&cfg));
     info!("Current theme spec\n{:?}", theme);
 }
 
-fn handle_edit_cmd(_ctx: &mut Context, editor: Option<String>, cli_cfg_path: Option<PathBuf>) {
+fn handle_edit_cmd(
+    _ctx: &mut Context,
+    editor: Option<String>,
+    cli_cfg_path: Option<PathBuf>,
+    bus: &SessionBus,
+) {
     info!("Running Edit command");
 
     trace!("Resolving config path");
     let config_path = if let Some(ref path) = cli_cfg_path {
         path.clone()
-    } else if let Some(ref path) = crate::config::default_path() {
+    } else if let Some(ref path) = config::default_path(bus) {
         path.clone()
     } else {
         warn!("Failed to resolve config path");
@@ -67,15 +183,15 @@ fn handle_edit_cmd(_ctx: &mut Context, editor: Option<String>, cli_cfg_path: Opt
 
     trace!("Resolving editor name");
     if let Some(ref editor_name) = editor {
-        open_editor(editor_name, config_path.borrow());
+        open_editor(bus, editor_name, config_path.borrow());
     } else if let Ok(editor_name) = std::env::var("EDITOR") {
-        open_editor(editor_name.borrow(), config_path.borrow());
+        open_editor(bus, editor_name.borrow(), config_path.borrow());
     } else {
         warn!("Failed to resolve editor name");
     }
 }
 
-fn open_editor(editor: &str, config_path: &Path) {
+fn open_editor(bus: &SessionBus, editor: &str, config_path: &Path) {
     info!(
         "Handling edit cmd with editor: {} for config: {}",
         editor,
@@ -89,7 +205,7 @@ fn open_editor(editor: &str, config_path: &Path) {
         return;
     }
 
-    match Command::new(editor).arg(config_path).status() {
+    match bus.user_command(editor).arg(config_path).status() {
         Ok(status) => {
             if status.success() {
                 info!("Editor closed properly with status code 0");
@@ -106,8 +222,210 @@ fn open_editor(editor: &str, config_path: &Path) {
     }
 }
 
-fn handle_lock_cmd(ctx: &mut Context) {
-    maybe_lock_or_unlock(ctx, "unsupported", true, false);
+fn handle_available_cmd(_ctx: &mut Context, json: bool, bus: &SessionBus) {
+    info!("Running Available command");
+
+    let installed = InstalledThemes::scan(bus);
+
+    if json {
+        match serde_json::to_string_pretty(&installed) {
+            Ok(output) => println!("{}", output),
+            Err(err) => error!("Failed to serialize installed themes with error: {}", err),
+        }
+        return;
+    }
+
+    let groups = [
+        ("GTK3 themes", &installed.gtk3),
+        ("GTK4 themes", &installed.gtk4),
+        ("Cinnamon themes", &installed.cinnamon),
+        ("Metacity themes", &installed.metacity),
+        ("GNOME Shell themes", &installed.gnome_shell),
+        ("Xfwm4 themes", &installed.xfwm4),
+        ("Icon themes", &installed.icons),
+        ("Cursor themes", &installed.cursors),
+        ("Font families", &installed.fonts),
+    ];
+
+    for (title, names) in groups {
+        println!("{}:", title);
+        for name in names {
+            println!("  {}", name);
+        }
+    }
+}
+
+pub fn handle_doctor_cmd(
+    ctx: &mut Context,
+    args: &Args,
+    bus: SessionBus,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Running Doctor command");
+
+    let cfg = match config::load_config(args, &bus) {
+        Ok(cfg) => Some(cfg),
+        Err(err) => {
+            println!("[FAIL] Config: {}", err);
+            println!("       remedy: pass valid config with --config or place it in the default location");
+            None
+        }
+    };
+
+    let config_dir = match &args.config {
+        Some(path) => path.parent().map(Path::to_path_buf),
+        None => bus.config_dir().map(|dir| dir.join(ConstantRepo::app_name())),
+    };
+
+    let selection = Selection::resolve(args.backend, cfg.as_ref().and_then(Config::backend), &bus);
+
+    let checks = doctor::run_checks(
+        cfg.as_ref(),
+        config_dir.as_deref(),
+        &ctx.data,
+        &selection,
+        backend::create(selection.kind, bus).as_ref(),
+    );
+    doctor::print_report(&checks);
+
+    let failed = checks.iter().filter(|check| !check.passed).count() + usize::from(cfg.is_none());
+    if failed > 0 {
+        return Err(format!("{} check(s) failed", failed).into());
+    }
+    Ok(())
+}
+
+fn handle_toggle_cmd(ctx: &mut Context, lock: bool, cfg: Config, backend: &dyn Backend) {
+    info!("Running Toggle command");
+
+    let pair = match cfg.toggle_pair() {
+        Some(pair) => pair,
+        None => {
+            error!("No toggle section found in config -- not taking any action");
+            return;
+        }
+    };
+
+    let (light, dark) = match (cfg.theme_for_name(&pair.light), cfg.theme_for_name(&pair.dark)) {
+        (Some(light), Some(dark)) => (light, dark),
+        _ => {
+            error!(
+                "Failed to find toggle themes for given names: {}, {}",
+                pair.light, pair.dark
+            );
+            return;
+        }
+    };
+
+    // Desktop theme tells the pair members apart most reliably. If it matches neither of them
+    // we fall back to the light / dark preference.
+    let current = backend::get_theme(backend);
+    let is_dark = if current.desktop == dark.spec.desktop && current.desktop != light.spec.desktop {
+        true
+    } else if current.desktop == light.spec.desktop && current.desktop != dark.spec.desktop {
+        false
+    } else {
+        current.color_scheme_preference == "prefer-dark"
+    };
+
+    let target = if is_dark { light } else { dark };
+    info!("Toggling theme to {}", target.name);
+    apply_theme(backend, &cfg, target);
+
+    if lock {
+        let until = cfg.next_span_boundary(Local::now());
+        match until {
+            Some(date) => info!("Locking theme {} until {}", target.name, date),
+            None => info!(
+                "Locking theme {} -- no time spans defined, lock does not expire",
+                target.name
+            ),
+        }
+        let _ = ctx.data.lock_theme(&target.name, until);
+    }
+}
+
+fn handle_list_cmd(ctx: &mut Context, json: bool, cfg: Config) {
+    info!("Running List command");
+
+    let now = Local::now();
+    let scheduled = cfg.theme_for_time(now).map(|theme| theme.name.as_str());
+    let locked = ctx
+        .data
+        .theme_lock()
+        .filter(|lock| !lock.is_expired(now))
+        .map(|lock| lock.theme);
+
+    let entries: Vec<ThemeListEntry> = cfg
+        .themes()
+        .iter()
+        .map(|theme| ThemeListEntry {
+            name: theme.name.clone(),
+            span: theme
+                .span
+                .as_ref()
+                .map(|span| format!("{}-{}", span.start(), span.stop())),
+            default: cfg.default_theme_name() == Some(theme.name.as_str()),
+            scheduled: scheduled == Some(theme.name.as_str()),
+            locked: locked.as_deref() == Some(theme.name.as_str()),
+        })
+        .collect();
+
+    if json {
+        match serde_json::to_string_pretty(&entries) {
+            Ok(output) => println!("{}", output),
+            Err(err) => error!("Failed to serialize theme list with error: {}", err),
+        }
+        return;
+    }
+
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!(
+        "{:name_width$}  {:11}  {:7}  {:9}  LOCKED",
+        "NAME", "SPAN", "DEFAULT", "SCHEDULED"
+    );
+    for entry in entries {
+        println!(
+            "{:name_width$}  {:11}  {:7}  {:9}  {}",
+            entry.name,
+            entry.span.unwrap_or_else(|| String::from("-")),
+            yes_no(entry.default),
+            yes_no(entry.scheduled),
+            yes_no(entry.locked),
+        );
+    }
+}
+
+#[derive(Serialize)]
+struct ThemeListEntry {
+    name: String,
+    span: Option<String>,
+    default: bool,
+    scheduled: bool,
+    locked: bool,
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn handle_lock_cmd(ctx: &mut Context, cfg: Config, backend: &dyn Backend) {
+    // Lock is recorded against theme that is currently applied, if it is one of configured themes
+    let theme = cfg
+        .themes()
+        .iter()
+        .find(|theme| backend::is_theme_applied(backend, theme))
+        .map(|theme| theme.name.as_str())
+        .unwrap_or("unknown");
+    maybe_lock_or_unlock(ctx, theme, true, false);
 }
 
 fn handle_unlock_cmd(ctx: &mut Context) {
@@ -117,7 +435,7 @@ fn handle_unlock_cmd(ctx: &mut Context) {
 fn maybe_lock_or_unlock(ctx: &mut Context, theme: &str, lock: bool, unlock: bool) {
     if lock {
         info!("LOCKING");
-        let _ = ctx.data.lock_theme(theme);
+        let _ = ctx.data.lock_theme(theme, None);
     } else if unlock {
         let _ = ctx.data.unlock_theme();
     }
@@ -125,5 +443,13 @@ fn maybe_lock_or_unlock(ctx: &mut Context, theme: &str, lock: bool, unlock: bool
 
 fn is_theme_locked(ctx: &mut Context) -> bool {
     // Alternatively we could use some small sqlite db here
-    ctx.data.theme_lock_file.is_file()
+    match ctx.data.theme_lock() {
+        Some(lock) if lock.is_expired(Local::now()) => {
+            info!("Theme lock on {} expired", lock.theme);
+            let _ = ctx.data.unlock_theme();
+            false
+        }
+        Some(_) => true,
+        None => false,
+    }
 }
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use crate::bus::SessionBus;
+
+/// Minimal editor of INI-style config files. Lines that are not touched (comments, ordering, other
+/// sections) are preserved as they are.
+#[derive(Debug, Default)]
+pub struct IniFile {
+    lines: Vec<String>,
+}
+
+/// Name of the section line opens, if it is a section header
+fn section_name(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with('[') && line.ends_with(']') {
+        Some(&line[1..line.len() - 1])
+    } else {
+        None
+    }
+}
+
+/// Key & value of an entry line
+fn entry(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') || line.starts_with(';') {
+        return None;
+    }
+    let (key, value) = line.split_once('=')?;
+    Some((key.trim(), value.trim()))
+}
+
+impl IniFile {
+    pub fn parse(content: &str) -> Self {
+        IniFile {
+            lines: content.lines().map(str::to_owned).collect(),
+        }
+    }
+
+    /// Loads file from disk. Missing file is treated as an empty one.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(IniFile::parse(&content)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(IniFile::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, bus: &SessionBus, path: &Path) -> Result<(), String> {
+        bus.write_file(path, self.to_string())
+    }
+
+    /// Returns value of `key` from the first section matching the predicate. Empty section name stands
+    /// for entries placed before any section header.
+    pub fn find(&self, section: impl Fn(&str) -> bool, key: &str) -> Option<String> {
+        let mut current = "";
+        for line in &self.lines {
+            if let Some(name) = section_name(line) {
+                current = name;
+            } else if let Some((entry_key, value)) = entry(line) {
+                if entry_key == key && section(current) {
+                    return Some(value.to_owned());
+                }
+            }
+        }
+        None
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<String> {
+        self.find(|name| name == section, key)
+    }
+
+    /// Sets value of `key` in `section`, adding the entry (and section) if it is missing
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let mut current = "";
+        // Index of the last non-empty line of the requested section, new entries are inserted after it
+        let mut section_end: Option<usize> = if section.is_empty() { Some(0) } else { None };
+
+        for (index, line) in self.lines.iter().enumerate() {
+            if let Some(name) = section_name(line) {
+                current = name;
+                if current == section {
+                    section_end = Some(index + 1);
+                }
+            } else if current == section {
+                if let Some((entry_key, _)) = entry(line) {
+                    if entry_key == key {
+                        self.lines[index] = format!("{}={}", key, value);
+                        return;
+                    }
+                }
+                if !line.trim().is_empty() {
+                    section_end = Some(index + 1);
+                }
+            }
+        }
+
+        match section_end {
+            Some(index) => self.lines.insert(index, format!("{}={}", key, value)),
+            None => {
+                if self.lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+                self.lines.push(format!("[{}]", section));
+                self.lines.push(format!("{}={}", key, value));
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for IniFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
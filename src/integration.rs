@@ -0,0 +1,174 @@
+mod alacritty;
+mod foot;
+mod gnome_terminal;
+mod kitty;
+mod kvantum;
+mod neovim;
+mod qtct;
+mod tmux;
+mod vscode;
+mod wezterm;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use log::{error, info, trace};
+
+use crate::{bus::SessionBus, doctor::Check, theme::AppTheme};
+
+/// Names applications are referred by in `apps` map of theme spec
+pub const NAMES: &[&str] = &[
+    alacritty::NAME,
+    foot::NAME,
+    gnome_terminal::NAME,
+    kitty::NAME,
+    kvantum::NAME,
+    neovim::NAME,
+    qtct::QT5CT_NAME,
+    qtct::QT6CT_NAME,
+    tmux::NAME,
+    vscode::VSCODE_NAME,
+    vscode::VSCODIUM_NAME,
+    wezterm::NAME,
+];
+
+/// First line of files fully managed by the tool
+const MANAGED_HEADER: &str = "Managed by theme-manager, manual changes will be overwritten";
+
+/// Applies themes to a particular application, e.g. terminal emulator or editor
+pub trait Integration {
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String>;
+
+    /// Theme currently used by the application
+    fn current(&self) -> Result<AppTheme, String>;
+
+    /// Checks whether theme is available to the application
+    fn validate(&self, _theme: &AppTheme) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Environment checks specific to the application, reported by `doctor` command
+    fn checks(&self) -> Vec<Check> {
+        Vec::new()
+    }
+}
+
+pub fn create(name: &str, bus: &SessionBus) -> Result<Box<dyn Integration>, String> {
+    match name {
+        alacritty::NAME => Ok(Box::new(alacritty::Alacritty::new(bus.clone()))),
+        foot::NAME => Ok(Box::new(foot::Foot::new(bus.clone()))),
+        gnome_terminal::NAME => Ok(Box::new(gnome_terminal::GnomeTerminal::new(bus.clone()))),
+        kitty::NAME => Ok(Box::new(kitty::Kitty::new(bus.clone()))),
+        kvantum::NAME => Ok(Box::new(kvantum::Kvantum::new(bus.clone()))),
+        neovim::NAME => Ok(Box::new(neovim::Neovim::new(bus.clone()))),
+        qtct::QT5CT_NAME => Ok(Box::new(qtct::QtCt::qt5(bus.clone()))),
+        qtct::QT6CT_NAME => Ok(Box::new(qtct::QtCt::qt6(bus.clone()))),
+        tmux::NAME => Ok(Box::new(tmux::Tmux::new(bus.clone()))),
+        vscode::VSCODE_NAME => Ok(Box::new(vscode::VsCode::code(bus.clone()))),
+        vscode::VSCODIUM_NAME => Ok(Box::new(vscode::VsCode::codium(bus.clone()))),
+        wezterm::NAME => Ok(Box::new(wezterm::WezTerm::new(bus.clone()))),
+        _ => Err(format!(
+            "Unknown application: {}. Supported ones are: {}",
+            name,
+            NAMES.join(", ")
+        )),
+    }
+}
+
+/// Applies themes of all applications listed in `apps`, skipping ones that already use requested theme
+pub fn apply_all(bus: &SessionBus, apps: &BTreeMap<String, AppTheme>) {
+    for (name, theme) in apps {
+        let integration = match create(name, bus) {
+            Ok(integration) => integration,
+            Err(err) => {
+                error!("{}", err);
+                continue;
+            }
+        };
+
+        if matches!(integration.current(), Ok(current) if theme.is_satisfied_by(&current)) {
+            trace!("{} theme already set to: {}", name, theme);
+            continue;
+        }
+
+        match integration.apply(theme) {
+            Ok(()) => info!("{} theme set to: {}", name, theme),
+            Err(err) => error!("Failed to set {} theme to: {}. {}", name, theme, err),
+        }
+    }
+}
+
+/// Retrieves themes currently used by given applications. Ones that failed to be retrieved hold error
+/// description.
+pub fn current_all<'a>(
+    bus: &SessionBus,
+    names: impl IntoIterator<Item = &'a str>,
+) -> BTreeMap<String, AppTheme> {
+    names
+        .into_iter()
+        .map(|name| {
+            let current = create(name, bus).and_then(|integration| integration.current());
+            (name.to_owned(), current.unwrap_or_else(AppTheme::Name))
+        })
+        .collect()
+}
+
+/// Config directory of given application, e.g. ~/.config/alacritty
+fn app_config_dir(bus: &SessionBus, app: &str) -> Result<PathBuf, String> {
+    bus.config_dir()
+        .map(|dir| dir.join(app))
+        .ok_or_else(|| "Failed to resolve config directory".to_owned())
+}
+
+/// Resolves path given in config. Leading `~` is expanded to home directory of the session owner, relative
+/// paths are resolved against `base`.
+pub fn resolve_path(bus: &SessionBus, base: &Path, path: &str) -> Result<PathBuf, String> {
+    if let Some(rest) = path.strip_prefix("~/") {
+        return bus
+            .home_dir()
+            .map(|home| home.join(rest))
+            .ok_or_else(|| "Failed to resolve home directory".to_owned());
+    }
+    Ok(base.join(path))
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))
+}
+
+/// Whether config mentions given file or module outside of comments, which start with `comment` marker and
+/// last until the end of line. Markers inside of strings are not told apart, which is good enough for hints
+/// reported by `doctor`.
+fn mentions(config: &str, comment: &str, name: &str) -> bool {
+    config
+        .lines()
+        .any(|line| line.split(comment).next().unwrap_or_default().contains(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mentions;
+
+    #[test]
+    fn finds_mentions_outside_of_comments() {
+        let config = "[general]\nimport = [\"~/.config/alacritty/colors.toml\"] # theme\n";
+        assert!(mentions(config, "#", "colors.toml"));
+        assert!(mentions(
+            "local m = require(\"theme_manager\")",
+            "--",
+            "theme_manager"
+        ));
+    }
+
+    #[test]
+    fn ignores_commented_out_mentions() {
+        assert!(!mentions("# import = [\"colors.toml\"]\n", "#", "colors.toml"));
+        assert!(!mentions("  #include=colors.ini", "#", "colors.ini"));
+        assert!(!mentions("-- require(\"theme_manager\")", "--", "theme_manager"));
+        assert!(!mentions("", "#", "colors.toml"));
+    }
+}
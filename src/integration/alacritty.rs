@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use crate::{bus::SessionBus, doctor::Check, theme::AppTheme};
+
+use super::Integration;
+
+pub const NAME: &str = "alacritty";
+
+const CONFIG_FILE: &str = "alacritty.toml";
+const COLORS_FILE: &str = "theme-manager-colors.toml";
+const SCHEME_PREFIX: &str = "# scheme: ";
+
+/// Alacritty terminal. Selected scheme file is copied to a managed colors file imported by alacritty.toml,
+/// which running instances pick up thanks to live config reload.
+pub struct Alacritty {
+    bus: SessionBus,
+}
+
+impl Alacritty {
+    pub fn new(bus: SessionBus) -> Self {
+        Alacritty { bus }
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, NAME)
+    }
+
+    /// Scheme path as given in config, relative ones are resolved against Alacritty config directory
+    fn scheme_path(&self, scheme: &str) -> Result<PathBuf, String> {
+        super::resolve_path(&self.bus, &self.config_dir()?, scheme)
+    }
+
+    /// Whether alacritty.toml imports the managed colors file, either in top level `import` or, since
+    /// Alacritty 0.14, in `general.import`
+    fn imports_colors_file(&self) -> Result<bool, String> {
+        let path = self.config_dir()?.join(CONFIG_FILE);
+        let config = super::read_file(&path)?;
+        Ok(super::mentions(&config, "#", COLORS_FILE))
+    }
+}
+
+impl Integration for Alacritty {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let scheme = theme.name();
+        let colors = super::read_file(&self.scheme_path(scheme)?)?;
+        let content = format!(
+            "# {}\n{}{}\n\n{}",
+            super::MANAGED_HEADER,
+            SCHEME_PREFIX,
+            scheme,
+            colors
+        );
+        self.bus
+            .write_file(&self.config_dir()?.join(COLORS_FILE), &content)
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.config_dir()?.join(COLORS_FILE);
+        super::read_file(&path)?
+            .lines()
+            .find_map(|line| line.strip_prefix(SCHEME_PREFIX))
+            .map(|scheme| AppTheme::Name(scheme.to_owned()))
+            .ok_or_else(|| format!("No scheme found in {}", path.display()))
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        let scheme = theme.name();
+        let path = self.scheme_path(scheme)?;
+        if path.is_file() {
+            Ok(())
+        } else {
+            Err(format!("Scheme file {} does not exist", path.display()))
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let name = format!("Alacritty {} import", COLORS_FILE);
+        let remedy = format!(
+            "add \"~/.config/alacritty/{}\" to `import` list in [general] section of {}",
+            COLORS_FILE, CONFIG_FILE
+        );
+        match self.imports_colors_file() {
+            Ok(true) => vec![Check::pass(name, format!("imported by {}", CONFIG_FILE))],
+            Ok(false) => vec![Check::fail(
+                name,
+                format!("not imported by {}", CONFIG_FILE),
+                remedy,
+            )],
+            Err(err) => vec![Check::fail(name, err, remedy)],
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::PathBuf;
+
+use log::{info, warn};
+
+use crate::{bus::SessionBus, doctor::Check, process, theme::AppTheme};
+
+use super::Integration;
+
+pub const NAME: &str = "foot";
+
+const CONFIG_FILE: &str = "foot.ini";
+const COLORS_FILE: &str = "theme-manager-colors.ini";
+const THEME_PREFIX: &str = "# theme: ";
+
+/// Color sets of foot along with signals switching running instances to them
+const SLOTS: [(&str, libc::c_int); 2] = [("colors", libc::SIGUSR1), ("colors2", libc::SIGUSR2)];
+
+/// Foot terminal. Managed file, included by the user's foot.ini, holds colors of up to two themes as `[colors]`
+/// and `[colors2]` sets. Foot does not reload its config, but switches between these sets on SIGUSR1 & SIGUSR2,
+/// so running instances follow as long as the theme was already in the file when they were started.
+pub struct Foot {
+    bus: SessionBus,
+}
+
+impl Foot {
+    pub fn new(bus: SessionBus) -> Self {
+        Foot { bus }
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, NAME)
+    }
+
+    /// Colors file path as given in config, relative ones are resolved against foot config directory
+    fn colors_path(&self, colors: &str) -> Result<PathBuf, String> {
+        super::resolve_path(&self.bus, &self.config_dir()?, colors)
+    }
+
+    /// Colors files held by the managed file, by slot, along with the slot that is currently used
+    fn slots(&self) -> ([Option<String>; 2], Option<usize>) {
+        let content = self
+            .config_dir()
+            .and_then(|dir| super::read_file(&dir.join(COLORS_FILE)))
+            .unwrap_or_default();
+        // Header ends with the first color set
+        let header: Vec<&str> = content
+            .lines()
+            .take_while(|line| !line.starts_with('['))
+            .collect();
+
+        let slots = SLOTS.map(|(section, _)| {
+            let prefix = format!("# {}: ", section);
+            header
+                .iter()
+                .find_map(|line| line.strip_prefix(&prefix))
+                .map(str::to_owned)
+        });
+        let active = header
+            .iter()
+            .find_map(|line| line.strip_prefix(THEME_PREFIX))
+            .and_then(|colors| slots.iter().position(|slot| slot.as_deref() == Some(colors)));
+        (slots, active)
+    }
+
+    /// Colors file rewritten as given color set. Color set headers are replaced, other sections are kept.
+    fn color_set(&self, colors: &str, section: &str) -> Result<String, String> {
+        let content = super::read_file(&self.colors_path(colors)?)?;
+        let body = content
+            .lines()
+            .filter(|line| !matches!(line.trim(), "[colors]" | "[colors2]"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(format!("[{}]\n{}\n", section, body))
+    }
+}
+
+impl Integration for Foot {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let colors = theme.name();
+        let (mut slots, active) = self.slots();
+
+        // Theme that is replaced takes the slot not in use, so that switching back works without restarting
+        let slot = match slots.iter().position(|slot| slot.as_deref() == Some(colors)) {
+            Some(slot) => slot,
+            None => {
+                let slot = active.map_or(0, |active| 1 - active);
+                slots[slot] = Some(colors.to_owned());
+                info!(
+                    "foot [{}] now holds {}, terminals started before keep their previous [{}] colors",
+                    SLOTS[slot].0, colors, SLOTS[slot].0
+                );
+                slot
+            }
+        };
+
+        let mut header = format!("# {}\n{}{}\n", super::MANAGED_HEADER, THEME_PREFIX, colors);
+        let mut sets = Vec::new();
+        for (index, name) in slots.iter().enumerate() {
+            let (section, _) = SLOTS[index];
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+            match self.color_set(name, section) {
+                Ok(set) => {
+                    header.push_str(&format!("# {}: {}\n", section, name));
+                    sets.push(set);
+                }
+                Err(err) if index == slot => return Err(err),
+                // Colors of the other theme are kept only as long as they can be read
+                Err(err) => warn!("Dropping foot [{}] colors {}: {}", section, name, err),
+            }
+        }
+        let content = format!("{}\n{}", header, sets.join("\n"));
+        self.bus
+            .write_file(&self.config_dir()?.join(COLORS_FILE), &content)?;
+
+        let (section, signal) = SLOTS[slot];
+        for foot in process::find_processes(NAME, self.bus.uid()) {
+            match foot.signal(signal) {
+                Ok(()) => info!("Switched foot ({}) to [{}] colors", foot.pid, section),
+                Err(err) => warn!("Failed to signal foot ({}): {}", foot.pid, err),
+            }
+        }
+        Ok(())
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.config_dir()?.join(COLORS_FILE);
+        super::read_file(&path)?
+            .lines()
+            .find_map(|line| line.strip_prefix(THEME_PREFIX))
+            .map(|colors| AppTheme::Name(colors.to_owned()))
+            .ok_or_else(|| format!("No colors file found in {}", path.display()))
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        let colors = theme.name();
+        let path = self.colors_path(colors)?;
+        if path.is_file() {
+            Ok(())
+        } else {
+            Err(format!("Colors file {} does not exist", path.display()))
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let name = format!("Foot {} include", COLORS_FILE);
+        let remedy = format!(
+            "add `include=~/.config/foot/{}` at the top of {}",
+            COLORS_FILE, CONFIG_FILE
+        );
+        let config = self
+            .config_dir()
+            .and_then(|dir| super::read_file(&dir.join(CONFIG_FILE)));
+
+        match config {
+            Ok(config) if super::mentions(&config, "#", COLORS_FILE) => {
+                vec![Check::pass(name, format!("included by {}", CONFIG_FILE))]
+            }
+            Ok(_) => vec![Check::fail(
+                name,
+                format!("not included by {}", CONFIG_FILE),
+                remedy,
+            )],
+            Err(err) => vec![Check::fail(name, err, remedy)],
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    gsettings::GSettings,
+    theme::AppTheme,
+};
+
+use super::Integration;
+
+pub const NAME: &str = "gnome-terminal";
+
+const PROFILES_LIST_SCHEMA: &str = "org.gnome.Terminal.ProfilesList";
+const PROFILE_SCHEMA: &str = "org.gnome.Terminal.Legacy.Profile";
+const PROFILES_PATH: &str = "/org/gnome/terminal/legacy/profiles:/";
+
+/// GNOME Terminal. Theme is a profile, referred by its visible name (or UUID), which is made the default one.
+/// Default profile is used by newly opened windows & tabs.
+pub struct GnomeTerminal {
+    gsettings: GSettings,
+}
+
+impl GnomeTerminal {
+    pub fn new(bus: SessionBus) -> Self {
+        GnomeTerminal {
+            gsettings: GSettings::new(bus),
+        }
+    }
+
+    /// Returns (UUID, visible name) pairs of all profiles
+    fn profiles(&self) -> Result<Vec<(String, String)>, String> {
+        // List is printed as ['uuid', ...], quotes are already stripped
+        let list = self.gsettings.get(PROFILES_LIST_SCHEMA, "list")?;
+        list.trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(str::trim)
+            .filter(|uuid| !uuid.is_empty())
+            .map(|uuid| {
+                let schema = format!("{}:{}:{}/", PROFILE_SCHEMA, PROFILES_PATH, uuid);
+                let name = self.gsettings.get(&schema, "visible-name")?;
+                Ok((uuid.to_owned(), name))
+            })
+            .collect()
+    }
+
+    fn find_profile(&self, profile: &str) -> Result<String, String> {
+        self.profiles()?
+            .into_iter()
+            .find(|(uuid, name)| name == profile || uuid == profile)
+            .map(|(uuid, _)| uuid)
+            .ok_or_else(|| format!("Profile {} does not exist", profile))
+    }
+}
+
+impl Integration for GnomeTerminal {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let profile = theme.name();
+        let uuid = self.find_profile(profile)?;
+        self.gsettings
+            .set(PROFILES_LIST_SCHEMA, "default", &format!("'{}'", uuid))
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let uuid = self.gsettings.get(PROFILES_LIST_SCHEMA, "default")?;
+        self.profiles()?
+            .into_iter()
+            .find(|(known, _)| *known == uuid)
+            .map(|(_, name)| AppTheme::Name(name))
+            .ok_or_else(|| format!("Default profile {} does not exist", uuid))
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        let profile = theme.name();
+        self.find_profile(profile).map(|_| ())
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let mut checks = vec![doctor::check_binary(
+            "gsettings",
+            "install the glib2 tools package providing gsettings",
+        )];
+        checks.extend(doctor::check_schema_keys(
+            &self.gsettings,
+            &[(PROFILES_LIST_SCHEMA, "default"), (PROFILES_LIST_SCHEMA, "list")],
+        ));
+        checks
+    }
+}
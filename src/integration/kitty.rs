@@ -0,0 +1,89 @@
+use std::{path::PathBuf, process::Stdio};
+
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    theme::AppTheme,
+    util,
+};
+
+use super::Integration;
+
+pub const NAME: &str = "kitty";
+
+/// Kitty terminal, themed with its `themes` kitten
+pub struct Kitty {
+    bus: SessionBus,
+}
+
+impl Kitty {
+    pub fn new(bus: SessionBus) -> Self {
+        Kitty { bus }
+    }
+
+    /// File the `themes` kitten writes selected theme to
+    fn current_theme_path(&self) -> Result<PathBuf, String> {
+        self.bus
+            .config_dir()
+            .map(|dir| dir.join("kitty").join("current-theme.conf"))
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())
+    }
+}
+
+impl Integration for Kitty {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let theme = theme.name();
+        let result = self
+            .bus
+            .command("kitty")
+            .arg("+kitten")
+            .arg("themes")
+            .arg("--reload-in=all")
+            .arg(theme)
+            .status();
+
+        util::check_status(result)
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.current_theme_path()?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+
+        // Theme files carry metadata in comments, e.g. "## name: Dark-mint-y"
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix("## name:"))
+            .map(|name| AppTheme::Name(name.trim().to_owned()))
+            .ok_or_else(|| format!("No theme name found in {}", path.display()))
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        let theme = theme.name();
+        let result = self
+            .bus
+            .command("kitty")
+            .arg("+kitten")
+            .arg("themes")
+            .arg("--dump-theme")
+            .arg(theme)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output();
+
+        util::handle_get_result(result)
+            .map(|_| ())
+            .map_err(|err| format!("Theme {} is not available: {}", theme, err))
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        vec![doctor::check_binary(
+            "kitty",
+            "install kitty or remove kitty themes from config",
+        )]
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    ini::IniFile,
+    theme::AppTheme,
+};
+
+use super::Integration;
+
+pub const NAME: &str = "kvantum";
+
+const CONFIG_FILE: &str = "kvantum.kvconfig";
+const SECTION: &str = "General";
+
+/// Kvantum, SVG-based Qt style. Selected theme is written to kvantum.kvconfig, Qt apps have to use
+/// "kvantum" style (see qt5ct & qt6ct integrations) and pick the change up when restarted.
+pub struct Kvantum {
+    bus: SessionBus,
+}
+
+impl Kvantum {
+    pub fn new(bus: SessionBus) -> Self {
+        Kvantum { bus }
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, "Kvantum")
+    }
+
+    /// Directories themes are looked up in, user's ones take precedence
+    fn theme_dirs(&self) -> Result<[PathBuf; 2], String> {
+        Ok([self.config_dir()?, PathBuf::from("/usr/share/Kvantum")])
+    }
+}
+
+impl Integration for Kvantum {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let path = self.config_dir()?.join(CONFIG_FILE);
+        let mut config =
+            IniFile::load(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        config.set(SECTION, "theme", theme.name());
+        config.save(&self.bus, &path)
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.config_dir()?.join(CONFIG_FILE);
+        let config =
+            IniFile::load(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        config
+            .get(SECTION, "theme")
+            .map(AppTheme::Name)
+            .ok_or_else(|| format!("Theme is not set in {}", path.display()))
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        let theme = theme.name();
+        let dirs = self.theme_dirs()?;
+        // Theme directory holds <theme>.kvconfig
+        let found = dirs
+            .iter()
+            .any(|dir| dir.join(theme).join(format!("{}.kvconfig", theme)).is_file());
+        if !found {
+            return Err(format!(
+                "Theme {} not found in {}",
+                theme,
+                dirs.iter().map(|dir| dir.display()).join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        vec![doctor::check_binary(
+            "kvantummanager",
+            "install Kvantum or remove kvantum themes from config",
+        )]
+    }
+}
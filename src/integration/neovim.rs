@@ -0,0 +1,192 @@
+use std::{
+    io::Write,
+    os::unix::{fs::FileTypeExt, net::UnixStream},
+    path::PathBuf,
+    time::Duration,
+};
+
+use log::{debug, warn};
+
+use crate::{bus::SessionBus, msgpack::Value, theme::AppTheme};
+
+use super::Integration;
+
+pub const NAME: &str = "nvim";
+
+/// Option of app theme holding `background` value
+const BACKGROUND_OPTION: &str = "background";
+const STATE_FILE: &str = "plugin/theme_manager.lua";
+const RPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Neovim. Colorscheme is switched in running instances over msgpack-RPC and written to a managed plugin
+/// file, which new instances source on startup.
+pub struct Neovim {
+    bus: SessionBus,
+}
+
+impl Neovim {
+    pub fn new(bus: SessionBus) -> Self {
+        Neovim { bus }
+    }
+
+    fn state_path(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, NAME).map(|dir| dir.join(STATE_FILE))
+    }
+
+    /// Sockets of running instances, named nvim.<pid>.<n> in runtime directory
+    fn sockets(&self) -> Vec<PathBuf> {
+        let runtime_dir = self.bus.runtime_dir();
+        let entries = match std::fs::read_dir(&runtime_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                debug!("Failed to read {}: {}", runtime_dir.display(), err);
+                return Vec::new();
+            }
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("nvim."))
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_socket()))
+            .map(|entry| entry.path())
+            .collect()
+    }
+
+    /// Runs Ex commands in the instance listening on given socket
+    fn run_commands(socket: &PathBuf, commands: &[String]) -> Result<(), String> {
+        let mut stream = UnixStream::connect(socket).map_err(|err| err.to_string())?;
+        stream
+            .set_read_timeout(Some(RPC_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(RPC_TIMEOUT)))
+            .map_err(|err| err.to_string())?;
+
+        for (id, command) in commands.iter().enumerate() {
+            // Request: [type = 0, msgid, method, params]
+            let request = Value::Array(vec![
+                Value::Int(0),
+                Value::Int(id as i64),
+                Value::Str("nvim_command".to_owned()),
+                Value::Array(vec![Value::Str(command.clone())]),
+            ]);
+            let mut buffer = Vec::new();
+            request.encode(&mut buffer);
+            stream.write_all(&buffer).map_err(|err| err.to_string())?;
+
+            // Response: [type = 1, msgid, error, result]. Notifications may arrive in between.
+            loop {
+                match Value::decode(&mut stream).map_err(|err| err.to_string())? {
+                    Value::Array(message) if message.first() == Some(&Value::Int(1)) => {
+                        match message.get(2) {
+                            Some(Value::Nil) | None => break,
+                            Some(error) => {
+                                return Err(format!("{} failed: {}", command, describe_error(error)))
+                            }
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn commands_for(theme: &AppTheme) -> Vec<String> {
+        // Background goes first, as colorschemes commonly pick their variant based on it
+        let mut commands = Vec::new();
+        if let Some(background) = theme.option(BACKGROUND_OPTION) {
+            commands.push(format!("set background={}", background));
+        }
+        commands.push(format!("colorscheme {}", theme.name()));
+        commands
+    }
+
+    fn write_state(&self, theme: &AppTheme) -> Result<(), String> {
+        let mut content = format!("-- {}\n", super::MANAGED_HEADER);
+        if let Some(background) = theme.option(BACKGROUND_OPTION) {
+            content += &format!("vim.o.background = {}\n", lua_string(background));
+        }
+        content += &format!("pcall(vim.cmd.colorscheme, {})\n", lua_string(theme.name()));
+        self.bus.write_file(&self.state_path()?, &content)
+    }
+}
+
+impl Integration for Neovim {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        self.write_state(theme)?;
+
+        let commands = Neovim::commands_for(theme);
+        for socket in self.sockets() {
+            match Neovim::run_commands(&socket, &commands) {
+                Ok(()) => debug!("Neovim at {} switched to {}", socket.display(), theme),
+                Err(err) => warn!("Failed to switch Neovim at {}: {}", socket.display(), err),
+            }
+        }
+        Ok(())
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.state_path()?;
+        let state = super::read_file(&path)?;
+
+        let name = state
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix("pcall(vim.cmd.colorscheme, ")?
+                    .strip_suffix(')')
+            })
+            .and_then(parse_lua_string)
+            .ok_or_else(|| format!("No colorscheme found in {}", path.display()))?;
+        let background = state
+            .lines()
+            .find_map(|line| line.strip_prefix("vim.o.background = "))
+            .and_then(parse_lua_string);
+
+        Ok(match background {
+            Some(background) => AppTheme::Detailed {
+                name,
+                options: [(BACKGROUND_OPTION.to_owned(), background)].into(),
+            },
+            None => AppTheme::Name(name),
+        })
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        if let AppTheme::Detailed { options, .. } = theme {
+            if let Some(key) = options.keys().find(|key| *key != BACKGROUND_OPTION) {
+                return Err(format!(
+                    "Unknown option {}, only {} is supported",
+                    key, BACKGROUND_OPTION
+                ));
+            }
+        }
+        match theme.option(BACKGROUND_OPTION) {
+            Some("dark" | "light") | None => Ok(()),
+            Some(background) => Err(format!("Background must be dark or light, not {}", background)),
+        }
+    }
+}
+
+/// Error is sent as [type, message] array
+fn describe_error(error: &Value) -> String {
+    match error {
+        Value::Array(parts) => match parts.get(1) {
+            Some(Value::Str(message)) => message.clone(),
+            _ => format!("{:?}", error),
+        },
+        Value::Str(message) => message.clone(),
+        _ => format!("{:?}", error),
+    }
+}
+
+fn lua_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn parse_lua_string(value: &str) -> Option<String> {
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some(value.replace("\\\"", "\"").replace("\\\\", "\\"))
+}
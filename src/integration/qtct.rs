@@ -0,0 +1,180 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use itertools::Itertools;
+
+use crate::{bus::SessionBus, doctor::Check, ini::IniFile, theme::AppTheme};
+
+use super::Integration;
+
+pub const QT5CT_NAME: &str = "qt5ct";
+pub const QT6CT_NAME: &str = "qt6ct";
+
+const SECTION: &str = "Appearance";
+/// Option of app theme holding color scheme, either name of a scheme from colors
+/// directory or path to one
+const COLOR_SCHEME_OPTION: &str = "color_scheme";
+/// Option of app theme holding icon theme
+const ICON_THEME_OPTION: &str = "icon_theme";
+const OPTIONS: &[&str] = &[COLOR_SCHEME_OPTION, ICON_THEME_OPTION];
+
+/// qt5ct & qt6ct, which theme Qt apps outside of KDE. Theme name is the widget style (e.g. "Fusion" or
+/// "kvantum"), color scheme & icon theme are optional. Without color scheme style's own palette is used.
+pub struct QtCt {
+    bus: SessionBus,
+    name: &'static str,
+}
+
+impl QtCt {
+    pub fn qt5(bus: SessionBus) -> Self {
+        QtCt {
+            bus,
+            name: QT5CT_NAME,
+        }
+    }
+
+    pub fn qt6(bus: SessionBus) -> Self {
+        QtCt {
+            bus,
+            name: QT6CT_NAME,
+        }
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, self.name)
+    }
+
+    fn config_path(&self) -> Result<PathBuf, String> {
+        Ok(self.config_dir()?.join(format!("{}.conf", self.name)))
+    }
+
+    /// Directories color schemes are looked up in, user's ones take precedence
+    fn color_dirs(&self) -> Result<[PathBuf; 2], String> {
+        Ok([
+            self.config_dir()?.join("colors"),
+            PathBuf::from("/usr/share").join(self.name).join("colors"),
+        ])
+    }
+
+    /// Resolves color scheme given in config. Names are looked up in color scheme directories, values ending
+    /// with `.conf` are treated as paths.
+    fn color_scheme_path(&self, scheme: &str) -> Result<PathBuf, String> {
+        if scheme.ends_with(".conf") {
+            let path = super::resolve_path(&self.bus, &self.config_dir()?.join("colors"), scheme)?;
+            if !path.is_file() {
+                return Err(format!("Color scheme {} does not exist", path.display()));
+            }
+            return Ok(path);
+        }
+
+        let dirs = self.color_dirs()?;
+        dirs.iter()
+            .map(|dir| dir.join(format!("{}.conf", scheme)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                format!(
+                    "Color scheme {} not found in {}",
+                    scheme,
+                    dirs.iter().map(|dir| dir.display()).join(", ")
+                )
+            })
+    }
+
+    /// Color scheme as it would be given in config: name for schemes from color scheme directories, path
+    /// otherwise
+    fn color_scheme_of(&self, path: &str) -> String {
+        let path = PathBuf::from(path);
+        let in_color_dir = self
+            .color_dirs()
+            .is_ok_and(|dirs| dirs.iter().any(|dir| path.parent() == Some(dir.as_path())));
+        match path.file_stem() {
+            Some(stem) if in_color_dir => stem.to_string_lossy().into_owned(),
+            _ => path.display().to_string(),
+        }
+    }
+
+    fn load(&self) -> Result<(PathBuf, IniFile), String> {
+        let path = self.config_path()?;
+        let config =
+            IniFile::load(&path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        Ok((path, config))
+    }
+}
+
+impl Integration for QtCt {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let (path, mut config) = self.load()?;
+        config.set(SECTION, "style", theme.name());
+        match theme.option(COLOR_SCHEME_OPTION) {
+            Some(scheme) => {
+                let scheme_path = self.color_scheme_path(scheme)?;
+                config.set(SECTION, "color_scheme_path", &scheme_path.to_string_lossy());
+                config.set(SECTION, "custom_palette", "true");
+            }
+            None => config.set(SECTION, "custom_palette", "false"),
+        }
+        if let Some(icon_theme) = theme.option(ICON_THEME_OPTION) {
+            config.set(SECTION, "icon_theme", icon_theme);
+        }
+        config.save(&self.bus, &path)
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let (path, config) = self.load()?;
+        let style = config
+            .get(SECTION, "style")
+            .ok_or_else(|| format!("Style is not set in {}", path.display()))?;
+
+        let mut options = BTreeMap::new();
+        if config.get(SECTION, "custom_palette").as_deref() == Some("true") {
+            if let Some(scheme_path) = config.get(SECTION, "color_scheme_path") {
+                options.insert(COLOR_SCHEME_OPTION.to_owned(), self.color_scheme_of(&scheme_path));
+            }
+        }
+        if let Some(icon_theme) = config.get(SECTION, "icon_theme") {
+            options.insert(ICON_THEME_OPTION.to_owned(), icon_theme);
+        }
+
+        if options.is_empty() {
+            return Ok(AppTheme::Name(style));
+        }
+        Ok(AppTheme::Detailed { name: style, options })
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        if let AppTheme::Detailed { options, .. } = theme {
+            if let Some(key) = options.keys().find(|key| !OPTIONS.contains(&key.as_str())) {
+                return Err(format!(
+                    "Unknown option {}, supported ones are: {}",
+                    key,
+                    OPTIONS.join(", ")
+                ));
+            }
+        }
+        match theme.option(COLOR_SCHEME_OPTION) {
+            Some(scheme) => self.color_scheme_path(scheme).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        // Environment of other user's session is not known
+        if self.bus.user.is_some() {
+            return Vec::new();
+        }
+
+        let name = format!("Qt platform theme for {}", self.name);
+        let remedy = format!(
+            "set QT_QPA_PLATFORMTHEME={} in the session environment",
+            self.name
+        );
+        match std::env::var("QT_QPA_PLATFORMTHEME") {
+            Ok(value) if value == self.name => vec![Check::pass(name, format!("set to {}", value))],
+            Ok(value) => vec![Check::fail(name, format!("set to {}", value), remedy)],
+            Err(_) => vec![Check::fail(name, "QT_QPA_PLATFORMTHEME is not set", remedy)],
+        }
+    }
+}
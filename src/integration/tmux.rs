@@ -0,0 +1,159 @@
+use std::{os::unix::fs::FileTypeExt, path::PathBuf, process::Stdio};
+
+use log::debug;
+
+use crate::{
+    bus::SessionBus,
+    doctor::{self, Check},
+    theme::AppTheme,
+    util,
+};
+
+use super::Integration;
+
+pub const NAME: &str = "tmux";
+
+const THEME_FILE: &str = "theme-manager.conf";
+const THEME_PREFIX: &str = "# theme: ";
+
+/// tmux. Selected theme file is copied to a managed conf file, sourced by the user's tmux.conf, and
+/// re-sourced by every running tmux server.
+pub struct Tmux {
+    bus: SessionBus,
+}
+
+impl Tmux {
+    pub fn new(bus: SessionBus) -> Self {
+        Tmux { bus }
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, NAME)
+    }
+
+    /// Theme file path as given in config, relative ones are resolved against tmux config directory
+    fn theme_path(&self, theme: &str) -> Result<PathBuf, String> {
+        super::resolve_path(&self.bus, &self.config_dir()?, theme)
+    }
+
+    /// tmux.conf in use, the legacy location takes precedence as it does in tmux
+    fn user_config_path(&self) -> Result<PathBuf, String> {
+        let legacy = self
+            .bus
+            .home_dir()
+            .map(|home| home.join(".tmux.conf"))
+            .ok_or_else(|| "Failed to resolve home directory".to_owned())?;
+        if legacy.is_file() {
+            return Ok(legacy);
+        }
+        Ok(self.config_dir()?.join("tmux.conf"))
+    }
+
+    /// Sockets of running servers, which live in $TMUX_TMPDIR/tmux-<uid> (/tmp by default)
+    fn server_sockets(&self) -> Vec<PathBuf> {
+        let tmp_dir = std::env::var_os("TMUX_TMPDIR")
+            .filter(|_| self.bus.user.is_none())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp"));
+        let socket_dir = tmp_dir.join(format!("tmux-{}", self.bus.uid()));
+
+        let entries = match std::fs::read_dir(&socket_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                debug!("Failed to read {}: {}", socket_dir.display(), err);
+                return Vec::new();
+            }
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_socket()))
+            .map(|entry| entry.path())
+            .collect()
+    }
+
+    fn source_in_running_servers(&self, path: &PathBuf) {
+        for socket in self.server_sockets() {
+            let result = self
+                .bus
+                .command("tmux")
+                .arg("-S")
+                .arg(&socket)
+                .arg("source-file")
+                .arg(path)
+                .stderr(Stdio::null())
+                .status();
+
+            // Sockets of servers that are no longer running are left behind, so failures are expected
+            if let Err(err) = util::check_status(result) {
+                debug!("Failed to reload tmux server at {}: {}", socket.display(), err);
+            }
+        }
+    }
+}
+
+impl Integration for Tmux {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let name = theme.name();
+        let content = super::read_file(&self.theme_path(name)?)?;
+        let content = format!(
+            "# {}\n{}{}\n\n{}",
+            super::MANAGED_HEADER,
+            THEME_PREFIX,
+            name,
+            content
+        );
+
+        let path = self.config_dir()?.join(THEME_FILE);
+        self.bus.write_file(&path, &content)?;
+        self.source_in_running_servers(&path);
+        Ok(())
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.config_dir()?.join(THEME_FILE);
+        super::read_file(&path)?
+            .lines()
+            .find_map(|line| line.strip_prefix(THEME_PREFIX))
+            .map(|theme| AppTheme::Name(theme.to_owned()))
+            .ok_or_else(|| format!("No theme found in {}", path.display()))
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        let path = self.theme_path(theme.name())?;
+        if path.is_file() {
+            Ok(())
+        } else {
+            Err(format!("Theme file {} does not exist", path.display()))
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let mut checks = vec![doctor::check_binary(
+            "tmux",
+            "install tmux or remove tmux themes from config",
+        )];
+
+        let name = format!("tmux {} source", THEME_FILE);
+        let remedy = format!(
+            "add `source-file -q ~/.config/tmux/{}` at the end of tmux.conf",
+            THEME_FILE
+        );
+        let config = self
+            .user_config_path()
+            .and_then(|path| super::read_file(&path).map(|config| (path, config)));
+
+        checks.push(match config {
+            Ok((path, config)) if super::mentions(&config, "#", THEME_FILE) => {
+                Check::pass(name, format!("sourced by {}", path.display()))
+            }
+            Ok((path, _)) => Check::fail(name, format!("not sourced by {}", path.display()), remedy),
+            Err(err) => Check::fail(name, err, remedy),
+        });
+        checks
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use crate::{bus::SessionBus, doctor::Check, jsonc::JsoncFile, theme::AppTheme};
+
+use super::Integration;
+
+pub const VSCODE_NAME: &str = "vscode";
+pub const VSCODIUM_NAME: &str = "vscodium";
+
+const COLOR_THEME_KEY: &str = "workbench.colorTheme";
+const ICON_THEME_KEY: &str = "workbench.iconTheme";
+/// Option of app theme holding icon theme
+const ICON_THEME_OPTION: &str = "icon_theme";
+
+/// VS Code & VSCodium. Color theme, and optionally icon theme, are written to user settings.json, which
+/// running editors watch for changes.
+pub struct VsCode {
+    bus: SessionBus,
+    name: &'static str,
+    /// Config directory of the editor, e.g. "Code" or "VSCodium"
+    config_dir: &'static str,
+}
+
+impl VsCode {
+    pub fn code(bus: SessionBus) -> Self {
+        VsCode {
+            bus,
+            name: VSCODE_NAME,
+            config_dir: "Code",
+        }
+    }
+
+    pub fn codium(bus: SessionBus) -> Self {
+        VsCode {
+            bus,
+            name: VSCODIUM_NAME,
+            config_dir: "VSCodium",
+        }
+    }
+
+    fn settings_path(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, self.config_dir).map(|dir| dir.join("User").join("settings.json"))
+    }
+}
+
+impl Integration for VsCode {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let path = self.settings_path()?;
+        let mut settings = JsoncFile::load(&path)?;
+        settings.set_string(COLOR_THEME_KEY, theme.name())?;
+        if let Some(icon_theme) = theme.option(ICON_THEME_OPTION) {
+            settings.set_string(ICON_THEME_KEY, icon_theme)?;
+        }
+        settings.save(&self.bus, &path)
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.settings_path()?;
+        let settings = JsoncFile::load(&path)?;
+        let name = settings
+            .get_string(COLOR_THEME_KEY)
+            .ok_or_else(|| format!("{} is not set in {}", COLOR_THEME_KEY, path.display()))?;
+
+        Ok(match settings.get_string(ICON_THEME_KEY) {
+            Some(icon_theme) => AppTheme::Detailed {
+                name,
+                options: [(ICON_THEME_OPTION.to_owned(), icon_theme)].into(),
+            },
+            None => AppTheme::Name(name),
+        })
+    }
+
+    fn validate(&self, theme: &AppTheme) -> Result<(), String> {
+        match theme {
+            AppTheme::Detailed { options, .. } => {
+                match options.keys().find(|key| *key != ICON_THEME_OPTION) {
+                    Some(key) => Err(format!(
+                        "Unknown option {}, only {} is supported",
+                        key, ICON_THEME_OPTION
+                    )),
+                    None => Ok(()),
+                }
+            }
+            AppTheme::Name(_) => Ok(()),
+        }
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let name = format!("{} settings", self.name);
+        let path = match self.settings_path() {
+            Ok(path) => path,
+            Err(err) => return vec![Check::fail(name, err, "make sure the config directory exists")],
+        };
+
+        match JsoncFile::load(&path) {
+            Ok(_) => vec![Check::pass(name, format!("{} is valid", path.display()))],
+            Err(err) => vec![Check::fail(name, err, "fix syntax errors in settings.json")],
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use crate::{bus::SessionBus, doctor::Check, theme::AppTheme};
+
+use super::Integration;
+
+pub const NAME: &str = "wezterm";
+
+const CONFIG_FILE: &str = "wezterm.lua";
+const MODULE: &str = "theme_manager";
+
+/// WezTerm terminal. Selected color scheme is written to a managed Lua module, which the user's wezterm.lua
+/// requires. WezTerm reloads its config whenever any of the required modules changes.
+pub struct WezTerm {
+    bus: SessionBus,
+}
+
+impl WezTerm {
+    pub fn new(bus: SessionBus) -> Self {
+        WezTerm { bus }
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        super::app_config_dir(&self.bus, NAME)
+    }
+
+    fn module_path(&self) -> Result<PathBuf, String> {
+        Ok(self.config_dir()?.join(format!("{}.lua", MODULE)))
+    }
+}
+
+impl Integration for WezTerm {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn apply(&self, theme: &AppTheme) -> Result<(), String> {
+        let scheme = theme.name();
+        let scheme = scheme.replace('\\', "\\\\").replace('"', "\\\"");
+        let content = format!(
+            "-- {}\nreturn {{\n    color_scheme = \"{}\",\n}}\n",
+            super::MANAGED_HEADER,
+            scheme
+        );
+        self.bus.write_file(&self.module_path()?, &content)
+    }
+
+    fn current(&self) -> Result<AppTheme, String> {
+        let path = self.module_path()?;
+        super::read_file(&path)?
+            .lines()
+            .find_map(|line| {
+                let value = line
+                    .trim()
+                    .strip_prefix("color_scheme")?
+                    .trim_start()
+                    .strip_prefix('=')?;
+                let value = value
+                    .trim()
+                    .trim_end_matches(',')
+                    .strip_prefix('"')?
+                    .strip_suffix('"')?;
+                Some(value.replace("\\\"", "\"").replace("\\\\", "\\"))
+            })
+            .map(AppTheme::Name)
+            .ok_or_else(|| format!("No color scheme found in {}", path.display()))
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        let name = format!("WezTerm {} module", MODULE);
+        let remedy = format!(
+            "set `config.color_scheme = require(\"{}\").color_scheme` in {}",
+            MODULE, CONFIG_FILE
+        );
+        let config = self
+            .config_dir()
+            .and_then(|dir| super::read_file(&dir.join(CONFIG_FILE)));
+
+        match config {
+            Ok(config) if super::mentions(&config, "--", MODULE) => {
+                vec![Check::pass(name, format!("required by {}", CONFIG_FILE))]
+            }
+            Ok(_) => vec![Check::fail(
+                name,
+                format!("not required by {}", CONFIG_FILE),
+                remedy,
+            )],
+            Err(err) => vec![Check::fail(name, err, remedy)],
+        }
+    }
+}
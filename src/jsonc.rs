@@ -0,0 +1,328 @@
+use std::{ops::Range, path::Path};
+
+use crate::bus::SessionBus;
+
+const DEFAULT_INDENT: &str = "    ";
+
+/// Minimal editor of JSON with comments (as used by VS Code settings). Only string values of top level keys
+/// are read & written, rest of the document (comments, formatting, other entries) is preserved as it is.
+#[derive(Debug)]
+pub struct JsoncFile {
+    content: String,
+}
+
+/// Top level entry of the document
+struct Entry {
+    key: String,
+    /// Byte range of the key, including quotes
+    key_span: Range<usize>,
+    /// Byte range of the value
+    value_span: Range<usize>,
+}
+
+impl JsoncFile {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let file = JsoncFile {
+            content: content.to_owned(),
+        };
+        file.entries()?;
+        Ok(file)
+    }
+
+    /// Loads file from disk. Missing or empty file is treated as an empty object.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) if !content.trim().is_empty() => JsoncFile::parse(&content)
+                .map_err(|err| format!("Failed to parse {}: {}", path.display(), err)),
+            Ok(_) => JsoncFile::parse("{}\n"),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => JsoncFile::parse("{}\n"),
+            Err(err) => Err(format!("Failed to read {}: {}", path.display(), err)),
+        }
+    }
+
+    pub fn save(&self, bus: &SessionBus, path: &Path) -> Result<(), String> {
+        bus.write_file(path, &self.content)
+    }
+
+    /// Returns value of top level key, if it is a string
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        let entries = self.entries().ok()?;
+        let entry = entries.iter().rev().find(|entry| entry.key == key)?;
+        serde_json::from_str(&self.content[entry.value_span.clone()]).ok()
+    }
+
+    /// Sets top level key to given string, replacing its current value or appending new entry
+    pub fn set_string(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = serde_json::to_string(value).map_err(|err| err.to_string())?;
+        let entries = self.entries()?;
+
+        // Later duplicates win, so the last occurrence is the one to replace
+        if let Some(entry) = entries.iter().rev().find(|entry| entry.key == key) {
+            self.content.replace_range(entry.value_span.clone(), &value);
+            return Ok(());
+        }
+
+        let key = serde_json::to_string(key).map_err(|err| err.to_string())?;
+        match entries.last() {
+            Some(last) => {
+                let indent = self.indent_of(last.key_span.start);
+                let insertion = format!(",\n{}{}: {}", indent, key, value);
+                self.content.insert_str(last.value_span.end, &insertion);
+            }
+            None => {
+                let open = self.content.find('{').ok_or("Document is not an object")?;
+                let close = self.content.rfind('}').ok_or("Document is not an object")?;
+                let inner = &self.content[open + 1..close];
+                // Keep comments placed inside the empty object
+                let inner = if inner.trim().is_empty() {
+                    ""
+                } else {
+                    inner.trim_end()
+                };
+                let replacement = format!("{{{}\n{}{}: {}\n}}", inner, DEFAULT_INDENT, key, value);
+                self.content.replace_range(open..close + 1, &replacement);
+            }
+        }
+        Ok(())
+    }
+
+    /// Indentation of the line containing given position, used to indent inserted entries alike
+    fn indent_of(&self, position: usize) -> String {
+        let line_start = self.content[..position].rfind('\n').map_or(0, |index| index + 1);
+        self.content[line_start..position]
+            .chars()
+            .take_while(|char| char.is_whitespace())
+            .collect()
+    }
+
+    fn entries(&self) -> Result<Vec<Entry>, String> {
+        let bytes = self.content.as_bytes();
+        let mut entries = Vec::new();
+
+        let mut index = skip_whitespace(bytes, 0);
+        if bytes.get(index) != Some(&b'{') {
+            return Err("Document is not an object".to_owned());
+        }
+        index += 1;
+
+        loop {
+            index = skip_whitespace(bytes, index);
+            match bytes.get(index) {
+                Some(b'}') => return Ok(entries),
+                Some(b'"') => {}
+                Some(_) => return Err(format!("Unexpected character at offset {}", index)),
+                None => return Err("Unexpected end of document".to_owned()),
+            }
+
+            let key_end = skip_string(bytes, index)?;
+            let key: String = serde_json::from_str(&self.content[index..key_end])
+                .map_err(|err| format!("Invalid key at offset {}: {}", index, err))?;
+            let key_span = index..key_end;
+
+            index = skip_whitespace(bytes, key_end);
+            if bytes.get(index) != Some(&b':') {
+                return Err(format!("Expected ':' at offset {}", index));
+            }
+            let value_start = skip_whitespace(bytes, index + 1);
+            let value_end = skip_value(bytes, value_start)?;
+            entries.push(Entry {
+                key,
+                key_span,
+                value_span: value_start..value_end,
+            });
+
+            index = skip_whitespace(bytes, value_end);
+            match bytes.get(index) {
+                // Trailing comma is allowed
+                Some(b',') => index += 1,
+                Some(b'}') => return Ok(entries),
+                _ => return Err(format!("Expected ',' or '}}' at offset {}", index)),
+            }
+        }
+    }
+}
+
+/// Skips whitespace & comments, returns position of the next meaningful character
+fn skip_whitespace(bytes: &[u8], mut index: usize) -> usize {
+    loop {
+        while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+            index += 1;
+        }
+        if bytes[index..].starts_with(b"//") {
+            while bytes.get(index).is_some_and(|&byte| byte != b'\n') {
+                index += 1;
+            }
+        } else if bytes[index..].starts_with(b"/*") {
+            index = match find(bytes, index + 2, b"*/") {
+                Some(end) => end + 2,
+                None => bytes.len(),
+            };
+        } else {
+            return index;
+        }
+    }
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+/// Returns position right after the string starting at `index`
+fn skip_string(bytes: &[u8], mut index: usize) -> Result<usize, String> {
+    let start = index;
+    index += 1;
+    while let Some(&byte) = bytes.get(index) {
+        match byte {
+            b'\\' => index += 2,
+            b'"' => return Ok(index + 1),
+            _ => index += 1,
+        }
+    }
+    Err(format!("Unterminated string at offset {}", start))
+}
+
+/// Returns position right after the value starting at `index`
+fn skip_value(bytes: &[u8], index: usize) -> Result<usize, String> {
+    match bytes.get(index) {
+        Some(b'"') => skip_string(bytes, index),
+        Some(b'{' | b'[') => {
+            let mut depth = 0;
+            let mut index = index;
+            loop {
+                index = skip_whitespace(bytes, index);
+                match bytes.get(index) {
+                    Some(b'"') => index = skip_string(bytes, index)?,
+                    Some(b'{' | b'[') => {
+                        depth += 1;
+                        index += 1;
+                    }
+                    Some(b'}' | b']') => {
+                        depth -= 1;
+                        index += 1;
+                        if depth == 0 {
+                            return Ok(index);
+                        }
+                    }
+                    Some(_) => index += 1,
+                    None => return Err("Unexpected end of document".to_owned()),
+                }
+            }
+        }
+        Some(_) => {
+            // Numbers & literals run until the next delimiter
+            let length = bytes[index..]
+                .iter()
+                .position(|&byte| matches!(byte, b',' | b'}' | b']' | b'/') || byte.is_ascii_whitespace())
+                .unwrap_or(bytes.len() - index);
+            Ok(index + length)
+        }
+        None => Err("Unexpected end of document".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsoncFile;
+
+    #[test]
+    fn reads_value_between_comments() {
+        let file = JsoncFile::parse(
+            "// leading comment\n{\n    /* block\n       comment */\n    \"a\": \"b\", // trailing\n    // \"c\": \"d\"\n}\n",
+        )
+        .unwrap();
+        assert_eq!(file.get_string("a").as_deref(), Some("b"));
+        assert_eq!(file.get_string("c"), None);
+    }
+
+    #[test]
+    fn reads_strings_with_comment_markers_and_quotes() {
+        let file = JsoncFile::parse(
+            r#"{ "url": "https://example.com", "quote": "say \"hi\" /* not a comment */", "a\"b": "c" }"#,
+        )
+        .unwrap();
+        assert_eq!(file.get_string("url").as_deref(), Some("https://example.com"));
+        assert_eq!(
+            file.get_string("quote").as_deref(),
+            Some(r#"say "hi" /* not a comment */"#)
+        );
+        assert_eq!(file.get_string("a\"b").as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn accepts_trailing_comma() {
+        let mut file = JsoncFile::parse("{\n    \"a\": 1,\n}\n").unwrap();
+        file.set_string("a", "x").unwrap();
+        assert_eq!(file.content, "{\n    \"a\": \"x\",\n}\n");
+    }
+
+    #[test]
+    fn rejects_malformed_document() {
+        assert!(JsoncFile::parse("[]").is_err());
+        assert!(JsoncFile::parse("{ \"a\": \"b\"").is_err());
+        assert!(JsoncFile::parse("{ \"a\" \"b\" }").is_err());
+        assert!(JsoncFile::parse("{ \"a\": \"b }").is_err());
+    }
+
+    #[test]
+    fn inserts_into_empty_object() {
+        let mut file = JsoncFile::parse("{}").unwrap();
+        file.set_string("a", "b").unwrap();
+        assert_eq!(file.content, "{\n    \"a\": \"b\"\n}");
+        assert_eq!(file.get_string("a").as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn keeps_comments_of_empty_object() {
+        let mut file = JsoncFile::parse("{\n    // nothing yet\n}\n").unwrap();
+        file.set_string("a", "b").unwrap();
+        assert_eq!(file.content, "{\n    // nothing yet\n    \"a\": \"b\"\n}\n");
+    }
+
+    #[test]
+    fn appends_missing_key_after_last_entry() {
+        let mut file = JsoncFile::parse("{\n\t\"a\": true, // keep\n\t\"b\": [1, 2]\n}\n").unwrap();
+        file.set_string("c", "d").unwrap();
+        assert_eq!(
+            file.content,
+            "{\n\t\"a\": true, // keep\n\t\"b\": [1, 2],\n\t\"c\": \"d\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn replaces_existing_value_only() {
+        let original = "{\n  // theme\n  \"workbench.colorTheme\" :  \"Light\" /* old */,\n  \"x\": 1\n}\n";
+        let mut file = JsoncFile::parse(original).unwrap();
+        file.set_string("workbench.colorTheme", "Dark").unwrap();
+        assert_eq!(file.content, original.replace("\"Light\"", "\"Dark\""));
+    }
+
+    #[test]
+    fn replaces_last_duplicate() {
+        let mut file = JsoncFile::parse("{ \"a\": \"1\", \"a\": \"2\" }").unwrap();
+        assert_eq!(file.get_string("a").as_deref(), Some("2"));
+        file.set_string("a", "3").unwrap();
+        assert_eq!(file.content, "{ \"a\": \"1\", \"a\": \"3\" }");
+    }
+
+    #[test]
+    fn ignores_nested_keys() {
+        let original = "{\n    \"[python]\": { \"a\": \"nested\" },\n    \"b\": \"c\"\n}\n";
+        let mut file = JsoncFile::parse(original).unwrap();
+        assert_eq!(file.get_string("a"), None);
+        file.set_string("a", "top").unwrap();
+        assert_eq!(
+            file.content,
+            "{\n    \"[python]\": { \"a\": \"nested\" },\n    \"b\": \"c\",\n    \"a\": \"top\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn escapes_written_values() {
+        let mut file = JsoncFile::parse("{}").unwrap();
+        file.set_string("a", "say \"hi\"").unwrap();
+        assert_eq!(file.get_string("a").as_deref(), Some("say \"hi\""));
+    }
+}
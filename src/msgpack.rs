@@ -0,0 +1,433 @@
+use std::io::Read;
+
+/// MessagePack value, limited to the types used by msgpack-RPC
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bin(Vec<u8>),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    /// Extension types (e.g. Neovim buffer & window handles) are kept as raw bytes
+    Ext(i8, Vec<u8>),
+}
+
+impl Value {
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Nil => out.push(0xc0),
+            Value::Bool(false) => out.push(0xc2),
+            Value::Bool(true) => out.push(0xc3),
+            Value::Int(value) => encode_int(*value, out),
+            Value::Float(value) => {
+                out.push(0xcb);
+                out.extend_from_slice(&value.to_be_bytes());
+            }
+            Value::Str(value) => {
+                encode_length(value.len(), 0xa0, 32, [0xd9, 0xda, 0xdb], out);
+                out.extend_from_slice(value.as_bytes());
+            }
+            Value::Bin(value) => {
+                encode_length(value.len(), 0, 0, [0xc4, 0xc5, 0xc6], out);
+                out.extend_from_slice(value);
+            }
+            Value::Array(items) => {
+                encode_length(items.len(), 0x90, 16, [0, 0xdc, 0xdd], out);
+                items.iter().for_each(|item| item.encode(out));
+            }
+            Value::Map(entries) => {
+                encode_length(entries.len(), 0x80, 16, [0, 0xde, 0xdf], out);
+                for (key, value) in entries {
+                    key.encode(out);
+                    value.encode(out);
+                }
+            }
+            Value::Ext(kind, data) => {
+                encode_length(data.len(), 0, 0, [0xc7, 0xc8, 0xc9], out);
+                out.push(*kind as u8);
+                out.extend_from_slice(data);
+            }
+        }
+    }
+
+    /// Reads single value from the stream
+    pub fn decode(reader: &mut impl Read) -> std::io::Result<Value> {
+        let marker = read_u8(reader)?;
+        let value = match marker {
+            0x00..=0x7f => Value::Int(marker as i64),
+            0x80..=0x8f => decode_map(reader, (marker & 0x0f) as usize)?,
+            0x90..=0x9f => decode_array(reader, (marker & 0x0f) as usize)?,
+            0xa0..=0xbf => decode_str(reader, (marker & 0x1f) as usize)?,
+            0xc0 => Value::Nil,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4 => {
+                let length = read_u8(reader)? as usize;
+                Value::Bin(read_bytes(reader, length)?)
+            }
+            0xc5 => {
+                let length = read_u16(reader)? as usize;
+                Value::Bin(read_bytes(reader, length)?)
+            }
+            0xc6 => {
+                let length = read_u32(reader)? as usize;
+                Value::Bin(read_bytes(reader, length)?)
+            }
+            0xc7 => {
+                let length = read_u8(reader)? as usize;
+                decode_ext(reader, length)?
+            }
+            0xc8 => {
+                let length = read_u16(reader)? as usize;
+                decode_ext(reader, length)?
+            }
+            0xc9 => {
+                let length = read_u32(reader)? as usize;
+                decode_ext(reader, length)?
+            }
+            0xca => Value::Float(f32::from_bits(read_u32(reader)?) as f64),
+            0xcb => Value::Float(f64::from_bits(read_u64(reader)?)),
+            0xcc => Value::Int(read_u8(reader)? as i64),
+            0xcd => Value::Int(read_u16(reader)? as i64),
+            0xce => Value::Int(read_u32(reader)? as i64),
+            0xcf => Value::Int(read_u64(reader)? as i64),
+            0xd0 => Value::Int(read_u8(reader)? as i8 as i64),
+            0xd1 => Value::Int(read_u16(reader)? as i16 as i64),
+            0xd2 => Value::Int(read_u32(reader)? as i32 as i64),
+            0xd3 => Value::Int(read_u64(reader)? as i64),
+            0xd4 => decode_ext(reader, 1)?,
+            0xd5 => decode_ext(reader, 2)?,
+            0xd6 => decode_ext(reader, 4)?,
+            0xd7 => decode_ext(reader, 8)?,
+            0xd8 => decode_ext(reader, 16)?,
+            0xd9 => {
+                let length = read_u8(reader)? as usize;
+                decode_str(reader, length)?
+            }
+            0xda => {
+                let length = read_u16(reader)? as usize;
+                decode_str(reader, length)?
+            }
+            0xdb => {
+                let length = read_u32(reader)? as usize;
+                decode_str(reader, length)?
+            }
+            0xdc => {
+                let length = read_u16(reader)? as usize;
+                decode_array(reader, length)?
+            }
+            0xdd => {
+                let length = read_u32(reader)? as usize;
+                decode_array(reader, length)?
+            }
+            0xde => {
+                let length = read_u16(reader)? as usize;
+                decode_map(reader, length)?
+            }
+            0xdf => {
+                let length = read_u32(reader)? as usize;
+                decode_map(reader, length)?
+            }
+            0xe0..=0xff => Value::Int(marker as i8 as i64),
+            0xc1 => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "invalid MessagePack marker 0xc1",
+                ))
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// Writes integer in the smallest representation
+fn encode_int(value: i64, out: &mut Vec<u8>) {
+    match value {
+        0..=0x7f => out.push(value as u8),
+        -32..=-1 => out.push(value as i8 as u8),
+        0x80..=0xff => {
+            out.push(0xcc);
+            out.push(value as u8);
+        }
+        0x100..=0xffff => {
+            out.push(0xcd);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xce);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        -0x80..=-33 => {
+            out.push(0xd0);
+            out.push(value as i8 as u8);
+        }
+        -0x8000..=-0x81 => {
+            out.push(0xd1);
+            out.extend_from_slice(&(value as i16).to_be_bytes());
+        }
+        -0x8000_0000..=-0x8001 => {
+            out.push(0xd2);
+            out.extend_from_slice(&(value as i32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xd3);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+/// Writes length header: fix variant (`fix_marker | length`) if length is below `fix_limit`, otherwise the
+/// smallest of 8, 16 & 32 bit variants. Zero marker means the variant does not exist for given type.
+fn encode_length(length: usize, fix_marker: u8, fix_limit: usize, markers: [u8; 3], out: &mut Vec<u8>) {
+    if length < fix_limit {
+        out.push(fix_marker | length as u8);
+    } else if length <= u8::MAX as usize && markers[0] != 0 {
+        out.push(markers[0]);
+        out.push(length as u8);
+    } else if length <= u16::MAX as usize {
+        out.push(markers[1]);
+        out.extend_from_slice(&(length as u16).to_be_bytes());
+    } else {
+        out.push(markers[2]);
+        out.extend_from_slice(&(length as u32).to_be_bytes());
+    }
+}
+
+fn decode_str(reader: &mut impl Read, length: usize) -> std::io::Result<Value> {
+    let bytes = read_bytes(reader, length)?;
+    Ok(Value::Str(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+fn decode_array(reader: &mut impl Read, length: usize) -> std::io::Result<Value> {
+    (0..length)
+        .map(|_| Value::decode(reader))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map(Value::Array)
+}
+
+fn decode_map(reader: &mut impl Read, length: usize) -> std::io::Result<Value> {
+    (0..length)
+        .map(|_| Ok((Value::decode(reader)?, Value::decode(reader)?)))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map(Value::Map)
+}
+
+fn decode_ext(reader: &mut impl Read, length: usize) -> std::io::Result<Value> {
+    let kind = read_u8(reader)? as i8;
+    Ok(Value::Ext(kind, read_bytes(reader, length)?))
+}
+
+/// Reads `length` bytes. Buffer grows with the data actually read, so that bogus length in a header can not
+/// exhaust memory.
+fn read_bytes(reader: &mut impl Read, length: usize) -> std::io::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    reader.by_ref().take(length as u64).read_to_end(&mut buffer)?;
+    if buffer.len() < length {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(buffer)
+}
+
+fn read_u8(reader: &mut impl Read) -> std::io::Result<u8> {
+    let mut buffer = [0; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> std::io::Result<u16> {
+    let mut buffer = [0; 2];
+    reader.read_exact(&mut buffer)?;
+    Ok(u16::from_be_bytes(buffer))
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_be_bytes(buffer))
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut buffer = [0; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(u64::from_be_bytes(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    fn encode(value: &Value) -> Vec<u8> {
+        let mut out = Vec::new();
+        value.encode(&mut out);
+        out
+    }
+
+    /// Encodes value, checks the header & decodes it back
+    fn round_trip(value: Value, header: &[u8]) {
+        let bytes = encode(&value);
+        assert!(
+            bytes.starts_with(header),
+            "{:?} encoded as {:02x?}, expected header {:02x?}",
+            value,
+            &bytes[..bytes.len().min(8)],
+            header
+        );
+        let mut reader = bytes.as_slice();
+        assert_eq!(Value::decode(&mut reader).unwrap(), value);
+        assert!(
+            reader.is_empty(),
+            "{:?} left {} bytes unread",
+            value,
+            reader.len()
+        );
+    }
+
+    fn string(length: usize) -> Value {
+        Value::Str("x".repeat(length))
+    }
+
+    #[test]
+    fn round_trips_integers() {
+        round_trip(Value::Int(0), &[0x00]);
+        round_trip(Value::Int(127), &[0x7f]);
+        round_trip(Value::Int(128), &[0xcc, 0x80]);
+        round_trip(Value::Int(255), &[0xcc, 0xff]);
+        round_trip(Value::Int(256), &[0xcd, 0x01, 0x00]);
+        round_trip(Value::Int(65535), &[0xcd]);
+        round_trip(Value::Int(65536), &[0xce]);
+        round_trip(Value::Int(u32::MAX as i64), &[0xce]);
+        round_trip(Value::Int(u32::MAX as i64 + 1), &[0xd3]);
+        round_trip(Value::Int(-1), &[0xff]);
+        round_trip(Value::Int(-32), &[0xe0]);
+        round_trip(Value::Int(-33), &[0xd0, 0xdf]);
+        round_trip(Value::Int(-128), &[0xd0, 0x80]);
+        round_trip(Value::Int(-129), &[0xd1]);
+        round_trip(Value::Int(-32768), &[0xd1]);
+        round_trip(Value::Int(-32769), &[0xd2]);
+        round_trip(Value::Int(i32::MIN as i64), &[0xd2]);
+        round_trip(Value::Int(i64::MIN), &[0xd3]);
+        round_trip(Value::Int(i64::MAX), &[0xd3]);
+    }
+
+    #[test]
+    fn round_trips_strings() {
+        round_trip(string(0), &[0xa0]);
+        round_trip(string(31), &[0xbf]);
+        round_trip(string(32), &[0xd9, 32]);
+        round_trip(string(255), &[0xd9, 0xff]);
+        round_trip(string(256), &[0xda, 0x01, 0x00]);
+        round_trip(string(65535), &[0xda, 0xff, 0xff]);
+        round_trip(string(65536), &[0xdb, 0x00, 0x01, 0x00, 0x00]);
+        round_trip(Value::Str("zażółć".to_owned()), &[0xa0 | 10]);
+    }
+
+    #[test]
+    fn round_trips_binary() {
+        round_trip(Value::Bin(vec![]), &[0xc4, 0]);
+        round_trip(Value::Bin(vec![7; 255]), &[0xc4, 0xff]);
+        round_trip(Value::Bin(vec![7; 256]), &[0xc5, 0x01, 0x00]);
+        round_trip(Value::Bin(vec![7; 65536]), &[0xc6]);
+    }
+
+    #[test]
+    fn round_trips_arrays() {
+        let array = |length: usize| Value::Array((0..length as i64).map(Value::Int).collect());
+        round_trip(array(0), &[0x90]);
+        round_trip(array(15), &[0x9f]);
+        round_trip(array(16), &[0xdc, 0x00, 0x10]);
+        round_trip(array(65535), &[0xdc, 0xff, 0xff]);
+        round_trip(array(65536), &[0xdd, 0x00, 0x01, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn round_trips_maps() {
+        let map = |length: usize| {
+            Value::Map(
+                (0..length as i64)
+                    .map(|index| (Value::Int(index), Value::Str(index.to_string())))
+                    .collect(),
+            )
+        };
+        round_trip(map(0), &[0x80]);
+        round_trip(map(15), &[0x8f]);
+        round_trip(map(16), &[0xde, 0x00, 0x10]);
+        round_trip(map(65536), &[0xdf, 0x00, 0x01, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn round_trips_other_values() {
+        round_trip(Value::Nil, &[0xc0]);
+        round_trip(Value::Bool(false), &[0xc2]);
+        round_trip(Value::Bool(true), &[0xc3]);
+        round_trip(Value::Float(1.5), &[0xcb]);
+        round_trip(Value::Ext(1, vec![0x2a]), &[0xc7, 1, 1]);
+        round_trip(
+            Value::Array(vec![
+                Value::Int(1),
+                Value::Int(7),
+                Value::Nil,
+                Value::Array(vec![Value::Ext(0, vec![1, 2]), Value::Str("ok".to_owned())]),
+            ]),
+            &[0x94],
+        );
+    }
+
+    #[test]
+    fn decodes_fixed_size_ext_and_float32() {
+        let mut reader: &[u8] = &[0xd4, 0x01, 0x05];
+        assert_eq!(Value::decode(&mut reader).unwrap(), Value::Ext(1, vec![5]));
+        let mut reader: &[u8] = &[0xca, 0x3f, 0xc0, 0x00, 0x00];
+        assert_eq!(Value::decode(&mut reader).unwrap(), Value::Float(1.5));
+    }
+
+    #[test]
+    fn fails_on_truncated_input() {
+        let values = [
+            Value::Int(300),
+            Value::Int(-40000),
+            Value::Float(2.5),
+            string(40),
+            string(300),
+            Value::Bin(vec![1, 2, 3]),
+            Value::Ext(3, vec![1, 2, 3]),
+            Value::Array(vec![Value::Int(1), string(20)]),
+            Value::Map(vec![(Value::Int(1), Value::Nil)]),
+        ];
+        for value in values {
+            let bytes = encode(&value);
+            for length in 0..bytes.len() {
+                let mut reader = &bytes[..length];
+                assert!(
+                    Value::decode(&mut reader).is_err(),
+                    "{:?} truncated to {} bytes decoded",
+                    value,
+                    length
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fails_on_bogus_lengths() {
+        // Headers announcing far more data than there is
+        for bytes in [
+            &[0xdb, 0xff, 0xff, 0xff, 0xff, b'x'][..],
+            &[0xc6, 0xff, 0xff, 0xff, 0xff][..],
+            &[0xc9, 0xff, 0xff, 0xff, 0xff, 0x01][..],
+            &[0xdd, 0xff, 0xff, 0xff, 0xff, 0x01][..],
+            &[0xdf, 0xff, 0xff, 0xff, 0xff, 0x01][..],
+        ] {
+            let mut reader = bytes;
+            assert!(Value::decode(&mut reader).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_reserved_marker() {
+        let mut reader: &[u8] = &[0xc1];
+        assert!(Value::decode(&mut reader).is_err());
+    }
+}
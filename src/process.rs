@@ -0,0 +1,81 @@
+use std::os::unix::fs::MetadataExt;
+
+use log::trace;
+
+/// Basic information on a running process, as read from procfs
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub uid: u32,
+}
+
+impl ProcessInfo {
+    /// Reads value of given environment variable of the process. This requires the process to be owned
+    /// by the current user (or root privileges).
+    pub fn env_var(&self, key: &str) -> Option<String> {
+        let environ = std::fs::read(format!("/proc/{}/environ", self.pid)).ok()?;
+        let prefix = format!("{}=", key);
+
+        environ
+            .split(|&byte| byte == 0)
+            .filter_map(|entry| std::str::from_utf8(entry).ok())
+            .find_map(|entry| entry.strip_prefix(&prefix).map(str::to_owned))
+    }
+
+    /// Command line arguments the process was started with
+    pub fn cmdline(&self) -> Vec<String> {
+        std::fs::read(format!("/proc/{}/cmdline", self.pid))
+            .map(|cmdline| {
+                cmdline
+                    .split(|&byte| byte == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sends signal to the process
+    pub fn signal(&self, signal: libc::c_int) -> std::io::Result<()> {
+        // SAFETY: kill has no memory safety preconditions
+        if unsafe { libc::kill(self.pid as libc::pid_t, signal) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+}
+
+/// Lists processes of given user with given name
+pub fn find_processes(name: &str, uid: u32) -> Vec<ProcessInfo> {
+    list_processes()
+        .into_iter()
+        .filter(|proc| proc.name == name && proc.uid == uid)
+        .collect()
+}
+
+/// Lists processes currently running in the system
+pub fn list_processes() -> Vec<ProcessInfo> {
+    let entries = match std::fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(err) => {
+            trace!("Failed to read /proc: {}", err);
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let uid = entry.metadata().ok()?.uid();
+            let name = std::fs::read_to_string(entry.path().join("comm")).ok()?;
+            Some(ProcessInfo {
+                pid,
+                name: name.trim().to_owned(),
+                uid,
+            })
+        })
+        .collect()
+}
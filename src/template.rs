@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use log::{error, info, trace};
+use serde::Deserialize;
+
+use crate::{bus::SessionBus, constant::ConstantRepo, integration, theme::Theme, util};
+
+/// Variable holding theme name, available to templates unless palette defines color of the same name
+const THEME_VARIABLE: &str = "theme";
+
+/// Config file of a tool without theme switching API (e.g. rofi, dunst or waybar), rendered from a template
+/// with colors of theme palette whenever theme is applied
+#[derive(Deserialize, Debug, Clone)]
+pub struct Template {
+    /// Template file, relative paths are resolved against ~/.config/theme-manager
+    pub source: String,
+    /// Rendered file, relative paths are resolved against home directory
+    pub target: String,
+    /// Shell command run after the target changed, e.g. `pkill -USR2 waybar`
+    pub reload: Option<String>,
+}
+
+impl Template {
+    pub fn source_path(&self, bus: &SessionBus) -> Result<PathBuf, String> {
+        let base = bus
+            .config_dir()
+            .map(|dir| dir.join(ConstantRepo::app_name()))
+            .ok_or_else(|| "Failed to resolve config directory".to_owned())?;
+        integration::resolve_path(bus, &base, &self.source)
+    }
+
+    pub fn target_path(&self, bus: &SessionBus) -> Result<PathBuf, String> {
+        let home = bus
+            .home_dir()
+            .ok_or_else(|| "Failed to resolve home directory".to_owned())?;
+        integration::resolve_path(bus, &home, &self.target)
+    }
+
+    /// Renders template with palette of given theme
+    pub fn render(&self, bus: &SessionBus, theme: &Theme) -> Result<String, String> {
+        let path = self.source_path(bus)?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        render(&content, theme).map_err(|err| format!("Failed to render {}: {}", path.display(), err))
+    }
+
+    fn apply(&self, bus: &SessionBus, theme: &Theme) -> Result<bool, String> {
+        let content = self.render(bus, theme)?;
+        let target = self.target_path(bus)?;
+        if std::fs::read_to_string(&target).is_ok_and(|current| current == content) {
+            return Ok(false);
+        }
+        bus.write_file(&target, &content)?;
+
+        if let Some(reload) = &self.reload {
+            let result = bus.command("sh").arg("-c").arg(reload).status();
+            util::check_status(result)
+                .map_err(|err| format!("Reload command `{}` failed: {}", reload, err))?;
+        }
+        Ok(true)
+    }
+}
+
+/// Renders all templates with palette of given theme, running reload commands of the ones that changed
+pub fn apply_all(bus: &SessionBus, templates: &[Template], theme: &Theme) {
+    for template in templates {
+        match template.apply(bus, theme) {
+            Ok(true) => info!("Template {} rendered to: {}", template.source, template.target),
+            Ok(false) => trace!(
+                "Template {} already rendered to: {}",
+                template.source,
+                template.target
+            ),
+            Err(err) => error!("Failed to render template {}. {}", template.source, err),
+        }
+    }
+}
+
+/// Opens placeholder
+const OPEN: &str = "{{";
+/// Stands for literal `{{`, for target formats using braces themselves (e.g. Go templates)
+const ESCAPED_OPEN: &str = "{{{{";
+
+/// Substitutes `{{ name }}` placeholders with palette colors. Filters may follow the name, e.g.
+/// `{{ background | strip }}` gives color without leading `#` and `{{ background | rgb }}` gives `26, 27, 38`.
+fn render(content: &str, theme: &Theme) -> Result<String, String> {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find(OPEN) {
+        output.push_str(&rest[..start]);
+        if rest[start..].starts_with(ESCAPED_OPEN) {
+            output.push_str(OPEN);
+            rest = &rest[start + ESCAPED_OPEN.len()..];
+            continue;
+        }
+        let end = rest[start..].find("}}").map(|end| start + end).ok_or_else(|| {
+            format!(
+                "Unterminated placeholder: {}",
+                rest[start..].lines().next().unwrap_or("")
+            )
+        })?;
+
+        let mut parts = rest[start + 2..end].split('|').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let mut value = match theme.spec.palette.get(name) {
+            Some(color) => color.clone(),
+            None if name == THEME_VARIABLE => theme.name.clone(),
+            None => {
+                return Err(format!(
+                    "Color {} is not defined in palette of theme {}",
+                    name, theme.name
+                ))
+            }
+        };
+        for filter in parts {
+            value = apply_filter(filter, &value)?;
+        }
+        output.push_str(&value);
+        rest = &rest[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn apply_filter(filter: &str, value: &str) -> Result<String, String> {
+    match filter {
+        "strip" => Ok(value.trim_start_matches('#').to_owned()),
+        "rgb" => {
+            let [red, green, blue] =
+                parse_hex_color(value).ok_or_else(|| format!("{} is not a #rgb or #rrggbb color", value))?;
+            Ok(format!("{}, {}, {}", red, green, blue))
+        }
+        _ => Err(format!(
+            "Unknown filter {}, supported ones are: strip, rgb",
+            filter
+        )),
+    }
+}
+
+/// Channels of `#rgb`, `#rrggbb` or `#rrggbbaa` color, alpha is ignored
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if !hex.chars().all(|char| char.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        3 => {
+            let mut channels = hex.chars().map(|digit| channel(&digit.to_string().repeat(2)));
+            Some([channels.next()??, channels.next()??, channels.next()??])
+        }
+        6 | 8 => Some([channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_filter, render};
+    use crate::theme::Theme;
+
+    fn theme() -> Theme {
+        serde_json::from_str(
+            r##"{
+                "name": "dark",
+                "spec": {
+                    "desktop": "X", "mouse": "X", "controls": "X", "icons": "X", "borders": "X",
+                    "wallpaper": "X", "color_scheme_preference": "prefer-dark",
+                    "palette": { "background": "#1a1b26", "accent": "#7aa2f7" }
+                }
+            }"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn substitutes_placeholders() {
+        assert_eq!(
+            render("bg={{background}}\nfg = {{ accent }};", &theme()).unwrap(),
+            "bg=#1a1b26\nfg = #7aa2f7;"
+        );
+        assert_eq!(
+            render("no placeholders } {", &theme()).unwrap(),
+            "no placeholders } {"
+        );
+    }
+
+    #[test]
+    fn substitutes_theme_name() {
+        assert_eq!(render("-- {{ theme }}", &theme()).unwrap(), "-- dark");
+    }
+
+    #[test]
+    fn applies_filters() {
+        assert_eq!(
+            render("{{ background | strip }} rgba({{ accent|rgb }}, 0.5)", &theme()).unwrap(),
+            "1a1b26 rgba(122, 162, 247, 0.5)"
+        );
+    }
+
+    #[test]
+    fn renders_escaped_braces() {
+        assert_eq!(
+            render("{{{{ .Name }} is {{ accent }}, {{{{{{ accent }}", &theme()).unwrap(),
+            "{{ .Name }} is #7aa2f7, {{#7aa2f7"
+        );
+    }
+
+    #[test]
+    fn fails_on_unknown_names() {
+        let err = render("{{ missing }}", &theme()).unwrap_err();
+        assert!(err.contains("missing"), "{}", err);
+        let err = render("{{ accent | upper }}", &theme()).unwrap_err();
+        assert!(err.contains("upper"), "{}", err);
+    }
+
+    #[test]
+    fn fails_on_unterminated_placeholder() {
+        assert!(render("a {{ accent", &theme()).is_err());
+    }
+
+    #[test]
+    fn strips_hash() {
+        assert_eq!(apply_filter("strip", "#abcdef").unwrap(), "abcdef");
+        assert_eq!(apply_filter("strip", "abcdef").unwrap(), "abcdef");
+    }
+
+    #[test]
+    fn converts_to_rgb() {
+        assert_eq!(apply_filter("rgb", "#1a1b26").unwrap(), "26, 27, 38");
+        assert_eq!(apply_filter("rgb", "1A1B26").unwrap(), "26, 27, 38");
+        assert_eq!(apply_filter("rgb", "#1a1b2680").unwrap(), "26, 27, 38");
+        assert_eq!(apply_filter("rgb", "#f80").unwrap(), "255, 136, 0");
+    }
+
+    #[test]
+    fn rejects_invalid_hex() {
+        for value in ["#12345", "#gggggg", "#+1+2+3", "red", "", "#", "#1a1b2"] {
+            assert!(apply_filter("rgb", value).is_err(), "{} accepted", value);
+        }
+    }
+}
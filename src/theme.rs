@@ -1,9 +1,12 @@
+mod component;
 mod theme_type;
 
-use chrono::{DateTime, Local, Timelike};
+pub use component::Component;
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Timelike};
 use itertools::Itertools;
 use serde::Deserialize;
-use std::{fmt::Display, path::PathBuf};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
 
 #[derive(Deserialize, Debug)]
 pub struct ThemeSpec {
@@ -13,8 +16,164 @@ pub struct ThemeSpec {
     pub icons: String,
     pub borders: String,
     pub wallpaper: PathBuf,
+    /// Legacy location of kitty theme, moved to `apps` once config is loaded
     pub kitty: Option<String>,
     pub color_scheme_preference: String,
+    /// Xresources fragment merged into X resource database, used only by x11 backend
+    pub xresources: Option<PathBuf>,
+    /// Fonts are given as Pango font descriptions, e.g. `Ubuntu 10` or `JetBrains Mono Bold 11`
+    pub interface_font: Option<String>,
+    pub document_font: Option<String>,
+    pub monospace_font: Option<String>,
+    pub titlebar_font: Option<String>,
+    pub night_light: Option<NightLight>,
+    /// Theme's gtk-4.0 directory or a single css file, linked into ~/.config/gtk-4.0 for libadwaita apps
+    pub gtk4_css: Option<PathBuf>,
+    /// Additional GSettings keys, used only by GSettings based backends
+    #[serde(default)]
+    pub gsettings: Vec<GSettingsEntry>,
+    /// Named colors substituted into templates, e.g. `"background": "#1a1b26"`
+    #[serde(default)]
+    pub palette: BTreeMap<String, String>,
+    /// Themes of applications, keyed by application name, e.g. `"kitty": "Dark-mint-y"`
+    #[serde(default)]
+    pub apps: BTreeMap<String, AppTheme>,
+}
+
+/// Theme of an application, given either as a plain name or as an object with `name` & extra options, e.g.
+/// `{ "name": "Default Dark Modern", "icon_theme": "vs-seti" }`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AppTheme {
+    Name(String),
+    Detailed {
+        name: String,
+        #[serde(flatten)]
+        options: BTreeMap<String, String>,
+    },
+}
+
+impl AppTheme {
+    pub fn name(&self) -> &str {
+        match self {
+            AppTheme::Name(name) | AppTheme::Detailed { name, .. } => name,
+        }
+    }
+
+    pub fn option(&self, key: &str) -> Option<&str> {
+        match self {
+            AppTheme::Name(_) => None,
+            AppTheme::Detailed { options, .. } => options.get(key).map(String::as_str),
+        }
+    }
+
+    /// Whether `current` theme has the same name & all the options of this one
+    pub fn is_satisfied_by(&self, current: &AppTheme) -> bool {
+        match self {
+            AppTheme::Name(name) => name == current.name(),
+            AppTheme::Detailed { name, options } => {
+                name == current.name()
+                    && options
+                        .iter()
+                        .all(|(key, value)| current.option(key) == Some(value.as_str()))
+            }
+        }
+    }
+}
+
+impl Display for AppTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppTheme::Name(name) => f.write_str(name),
+            AppTheme::Detailed { name, options } => {
+                let options = options
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .join(", ");
+                write!(f, "{} ({})", name, options)
+            }
+        }
+    }
+}
+
+/// Display color temperature settings
+#[derive(Deserialize, Debug, Clone)]
+pub struct NightLight {
+    pub enabled: bool,
+    /// Color temperature in Kelvin, desktop default is kept if not given
+    pub temperature: Option<u32>,
+}
+
+/// Arbitrary GSettings key set along with theme
+#[derive(Deserialize, Debug, Clone)]
+pub struct GSettingsEntry {
+    pub schema: String,
+    pub key: String,
+    /// Value in GVariant text format, e.g. `'Ubuntu 11'`, `1.25`, `uint32 5` or `['a', 'b']`
+    #[serde(with = "gvariant")]
+    pub value: String,
+}
+
+impl ThemeSpec {
+    /// Moves values of legacy fields to their current location
+    pub fn migrate(&mut self) {
+        if let Some(kitty) = self.kitty.take() {
+            self.apps
+                .entry("kitty".to_owned())
+                .or_insert(AppTheme::Name(kitty));
+        }
+    }
+
+    pub fn value_of(&self, component: &Component) -> Option<String> {
+        match component {
+            Component::Desktop => Some(self.desktop.clone()),
+            Component::Mouse => Some(self.mouse.clone()),
+            Component::Controls => Some(self.controls.clone()),
+            Component::Icons => Some(self.icons.clone()),
+            Component::Borders => Some(self.borders.clone()),
+            Component::Wallpaper => Some(self.wallpaper.to_string_lossy().into_owned()),
+            Component::ColorScheme => Some(self.color_scheme_preference.clone()),
+            Component::Xresources => self
+                .xresources
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned()),
+            Component::InterfaceFont => self.interface_font.clone(),
+            Component::DocumentFont => self.document_font.clone(),
+            Component::MonospaceFont => self.monospace_font.clone(),
+            Component::TitlebarFont => self.titlebar_font.clone(),
+            Component::NightLight => self
+                .night_light
+                .as_ref()
+                .map(|night_light| night_light.enabled.to_string()),
+            Component::NightLightTemperature => self
+                .night_light
+                .as_ref()
+                .and_then(|night_light| night_light.temperature)
+                .map(|temperature| temperature.to_string()),
+            Component::GSettings { schema, key } => self
+                .gsettings
+                .iter()
+                .find(|entry| entry.schema == *schema && entry.key == *key)
+                .map(|entry| entry.value.clone()),
+        }
+    }
+
+    /// Returns all components defined by this spec along with their values
+    pub fn components(&self) -> Vec<(Component, String)> {
+        let extra_keys = self.gsettings.iter().map(|entry| Component::GSettings {
+            schema: entry.schema.clone(),
+            key: entry.key.clone(),
+        });
+
+        Component::ALL
+            .into_iter()
+            .chain(extra_keys)
+            .filter_map(|component| {
+                let value = self.value_of(&component)?;
+                Some((component, value))
+            })
+            .collect()
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, Copy)]
@@ -46,6 +205,29 @@ impl TryFrom<String> for TimeSpec {
     }
 }
 
+impl TimeSpec {
+    /// Returns the first moment strictly after `date` at which the clock shows this time
+    pub fn next_occurrence(&self, date: DateTime<Local>) -> Option<DateTime<Local>> {
+        let time = NaiveTime::from_hms_opt(self.hour, self.minute, 0)?;
+        let today = Local
+            .from_local_datetime(&date.date_naive().and_time(time))
+            .earliest();
+
+        match today {
+            Some(occurrence) if occurrence > date => Some(occurrence),
+            _ => Local
+                .from_local_datetime(&(date.date_naive() + Duration::days(1)).and_time(time))
+                .earliest(),
+        }
+    }
+}
+
+impl Display for TimeSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
 impl From<DateTime<Local>> for TimeSpec {
     fn from(date: DateTime<Local>) -> Self {
         TimeSpec {
@@ -64,6 +246,14 @@ pub struct TimeSpan {
 }
 
 impl TimeSpan {
+    pub fn start(&self) -> TimeSpec {
+        self.start
+    }
+
+    pub fn stop(&self) -> TimeSpec {
+        self.stop
+    }
+
     pub fn contains(&self, timespec: &TimeSpec) -> bool {
         #![allow(clippy::comparison_chain)]
         if self.start.hour < self.stop.hour {
@@ -104,7 +294,7 @@ impl Display for ParseError {
 impl std::error::Error for ParseError {}
 
 impl serde::de::Error for ParseError {
-    fn custom<T: Display>(msg: T) -> Self
+    fn custom<T>(msg: T) -> Self
     where
         T: std::fmt::Display,
     {
@@ -133,7 +323,41 @@ mod timespec {
             Ok(timespec)
         } else {
             // https://stackoverflow.com/questions/66230715/make-my-own-error-for-serde-json-deserialize
-            Err(ParseError::InvalidDateFormat).map_err(D::Error::custom)
+            Err(D::Error::custom(ParseError::InvalidDateFormat))
         }
     }
 }
+
+mod gvariant {
+    use serde::{de::Error, Deserialize, Deserializer};
+    use serde_json::Value;
+
+    /// Converts JSON value to GVariant text format. Strings are passed as they are, so that any GVariant
+    /// value can be expressed with them.
+    fn to_gvariant(value: &Value) -> Result<String, String> {
+        match value {
+            Value::String(text) => Ok(text.clone()),
+            Value::Bool(flag) => Ok(flag.to_string()),
+            Value::Number(number) => Ok(number.to_string()),
+            Value::Array(items) => {
+                let items: Result<Vec<String>, String> = items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(text) => Ok(format!("'{}'", text.replace('\'', "\\'"))),
+                        other => to_gvariant(other),
+                    })
+                    .collect();
+                Ok(format!("[{}]", items?.join(", ")))
+            }
+            other => Err(format!("unsupported GSettings value: {}", other)),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        to_gvariant(&value).map_err(D::Error::custom)
+    }
+}
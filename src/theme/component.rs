@@ -0,0 +1,76 @@
+use std::fmt::Display;
+
+/// Part of the desktop look described by theme spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    Desktop,
+    Mouse,
+    Controls,
+    Icons,
+    Borders,
+    Wallpaper,
+    ColorScheme,
+    Xresources,
+    InterfaceFont,
+    DocumentFont,
+    MonospaceFont,
+    TitlebarFont,
+    NightLight,
+    NightLightTemperature,
+    /// Arbitrary GSettings key
+    GSettings {
+        schema: String,
+        key: String,
+    },
+}
+
+impl Component {
+    pub const ALL: [Component; 14] = [
+        Component::Desktop,
+        Component::Mouse,
+        Component::Controls,
+        Component::Icons,
+        Component::Borders,
+        Component::Wallpaper,
+        Component::ColorScheme,
+        Component::Xresources,
+        Component::InterfaceFont,
+        Component::DocumentFont,
+        Component::MonospaceFont,
+        Component::TitlebarFont,
+        Component::NightLight,
+        Component::NightLightTemperature,
+    ];
+
+    pub fn is_font(&self) -> bool {
+        matches!(
+            self,
+            Component::InterfaceFont
+                | Component::DocumentFont
+                | Component::MonospaceFont
+                | Component::TitlebarFont
+        )
+    }
+}
+
+impl Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Component::Desktop => f.write_str("Desktop theme"),
+            Component::Mouse => f.write_str("Mouse theme"),
+            Component::Controls => f.write_str("Controls theme"),
+            Component::Icons => f.write_str("Icons theme"),
+            Component::Borders => f.write_str("Borders theme"),
+            Component::Wallpaper => f.write_str("Wallpaper"),
+            Component::ColorScheme => f.write_str("Theme preference"),
+            Component::Xresources => f.write_str("Xresources"),
+            Component::InterfaceFont => f.write_str("Interface font"),
+            Component::DocumentFont => f.write_str("Document font"),
+            Component::MonospaceFont => f.write_str("Monospace font"),
+            Component::TitlebarFont => f.write_str("Titlebar font"),
+            Component::NightLight => f.write_str("Night light"),
+            Component::NightLightTemperature => f.write_str("Night light temperature"),
+            Component::GSettings { schema, key } => write!(f, "GSettings key {} {}", schema, key),
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use std::path::PathBuf;
+use std::{
+    ffi::CString,
+    os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+    path::{Path, PathBuf},
+    process::{ExitStatus, Output},
+};
 
 pub fn file_exists(path: &str) -> Result<PathBuf, String> {
     let path_buf = PathBuf::from(path);
@@ -9,3 +14,54 @@ pub fn file_exists(path: &str) -> Result<PathBuf, String> {
 
     Ok(path_buf)
 }
+
+/// Looks up executable of given name in directories listed in $PATH
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(name))
+        .find(|candidate| {
+            candidate
+                .metadata()
+                .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+}
+
+/// Checks whether current process is allowed to write to given path
+pub fn is_writable(path: &Path) -> bool {
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+/// Converts outcome of running a process into error description in case it failed
+pub fn check_status(result: std::io::Result<ExitStatus>) -> Result<(), String> {
+    match result {
+        Ok(status) => {
+            if status.success() {
+                Ok(())
+            } else if let Some(ret_code) = status.code() {
+                Err(format!("Process returned non-zero return code: {}", ret_code))
+            } else {
+                Err("Process was most likely interrupted".to_owned())
+            }
+        }
+        Err(err) => Err(format!("Failed to execute the process with error: {}", err)),
+    }
+}
+
+/// Extracts stdout of successfully finished process, with quotes stripped
+pub fn handle_get_result(result: std::io::Result<Output>) -> Result<String, String> {
+    match result {
+        Ok(output) if !output.status.success() => {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_owned())
+        }
+        Ok(output) => match String::from_utf8(output.stdout) {
+            Ok(stdout) => Ok(stdout.replace('\'', "").trim().to_owned()),
+            Err(err) => Err(err.to_string()),
+        },
+        Err(err) => Err(err.to_string()),
+    }
+}